@@ -0,0 +1,301 @@
+use std::path::PathBuf;
+
+use clap::parser::ValueSource;
+use clap::{ArgMatches, CommandFactory, FromArgMatches};
+use num_bigint::BigUint;
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer};
+
+use crate::program_input::{FeltInputValidation, InputFormat};
+use crate::{
+    parse_input_namespace, parse_range_modulus, validate_layout, Args, ArtifactFormat, Error,
+    ErrorFormat, FeltOutputFormat, MemoryFormat, PieCompression,
+};
+
+/// Default CLI arguments read from a `--config` TOML file. The keys mirror
+/// the fields of `Args`, except the program file and the flags selecting
+/// another mode than running it (`--program_info`, `--list_hints`, ...),
+/// which are command-line only.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    pub program_input: Option<PathBuf>,
+    pub program_input_base64: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_input_namespaces")]
+    pub program_input_ns: Option<Vec<(String, PathBuf)>>,
+    pub input_format: Option<InputFormat>,
+    pub felt_input_validation: Option<FeltInputValidation>,
+    #[serde(default, deserialize_with = "deserialize_range_modulus")]
+    pub input_range_modulus: Option<BigUint>,
+    pub input_schema: Option<PathBuf>,
+    pub resolve_env: Option<bool>,
+    pub trace_file: Option<PathBuf>,
+    pub print_output: Option<bool>,
+    #[serde(alias = "output_radix")]
+    pub felt_output_format: Option<FeltOutputFormat>,
+    pub emit_output_felts: Option<PathBuf>,
+    pub entrypoint: Option<String>,
+    pub memory_file: Option<PathBuf>,
+    pub trace_format: Option<ArtifactFormat>,
+    pub trace_include_registers: Option<bool>,
+    pub memory_format: Option<MemoryFormat>,
+    pub compress_artifacts: Option<bool>,
+    pub memory_holes_json: Option<PathBuf>,
+    pub trace_buffer_size: Option<usize>,
+    pub memory_buffer_size: Option<usize>,
+    pub layout: Option<String>,
+    pub proof_mode: Option<bool>,
+    pub secure_run: Option<bool>,
+    pub air_public_input: Option<String>,
+    pub air_private_input: Option<String>,
+    pub cairo_pie_output: Option<String>,
+    pub cairo_pie_compression: Option<PieCompression>,
+    pub manifest: Option<PathBuf>,
+    pub manifest_hashes: Option<bool>,
+    pub allow_missing_builtins: Option<bool>,
+    pub no_output_on_error: Option<bool>,
+    pub best_effort: Option<bool>,
+    pub handle_interrupt: Option<bool>,
+    pub progress: Option<bool>,
+    pub max_memory_cells: Option<usize>,
+    pub output_cap: Option<usize>,
+    pub builtin_hints: Option<bool>,
+    pub strict_alloc: Option<bool>,
+    pub seed: Option<u64>,
+    pub require_hints: Option<bool>,
+    pub fail_on_warnings: Option<bool>,
+    pub warn_unfilled_output: Option<bool>,
+    pub error_format: Option<ErrorFormat>,
+    pub repeat: Option<usize>,
+    pub print_trace_summary: Option<bool>,
+    pub print_builtin_counts: Option<bool>,
+}
+
+impl Config {
+    pub fn from_toml(input: &str) -> Result<Self, Error> {
+        let config: Config = toml::from_str(input).map_err(|e| Error::Config(e.to_string()))?;
+        if let Some(ref layout) = config.layout {
+            validate_layout(layout).map_err(Error::Config)?;
+        }
+        if config.program_input.is_some() && config.program_input_base64.is_some() {
+            return Err(Error::Config(
+                "program_input and program_input_base64 are mutually exclusive".to_string(),
+            ));
+        }
+        Ok(config)
+    }
+
+    // Fills in the arguments which were not given explicitly on the command line.
+    fn apply(self, args: &mut Args, matches: &ArgMatches) {
+        let missing = |id: &str| matches.value_source(id) != Some(ValueSource::CommandLine);
+
+        macro_rules! fill {
+            ($field:ident) => {
+                if let Some(value) = self.$field {
+                    if missing(stringify!($field)) {
+                        args.$field = value.into();
+                    }
+                }
+            };
+        }
+
+        // The input sources exclude each other, so one given on the command
+        // line replaces either of them in the file.
+        if missing("program_input") && missing("program_input_base64") {
+            fill!(program_input);
+            fill!(program_input_base64);
+        }
+        fill!(program_input_ns);
+        fill!(input_format);
+        fill!(felt_input_validation);
+        fill!(input_range_modulus);
+        fill!(input_schema);
+        fill!(resolve_env);
+        fill!(trace_file);
+        fill!(print_output);
+        fill!(felt_output_format);
+        fill!(emit_output_felts);
+        fill!(entrypoint);
+        fill!(memory_file);
+        fill!(trace_format);
+        fill!(trace_include_registers);
+        fill!(memory_format);
+        fill!(compress_artifacts);
+        fill!(memory_holes_json);
+        fill!(trace_buffer_size);
+        fill!(memory_buffer_size);
+        fill!(layout);
+        fill!(proof_mode);
+        fill!(secure_run);
+        fill!(air_public_input);
+        fill!(air_private_input);
+        fill!(cairo_pie_output);
+        fill!(cairo_pie_compression);
+        fill!(manifest);
+        fill!(manifest_hashes);
+        fill!(allow_missing_builtins);
+        fill!(no_output_on_error);
+        fill!(best_effort);
+        fill!(handle_interrupt);
+        fill!(progress);
+        fill!(max_memory_cells);
+        fill!(output_cap);
+        fill!(builtin_hints);
+        fill!(strict_alloc);
+        fill!(seed);
+        fill!(require_hints);
+        fill!(fail_on_warnings);
+        fill!(warn_unfilled_output);
+        fill!(error_format);
+        fill!(repeat);
+        fill!(print_trace_summary);
+        fill!(print_builtin_counts);
+    }
+}
+
+// Reads an `input_range_modulus`, given as a string like on the command line
+// since the moduli of interest do not fit in a TOML integer.
+fn deserialize_range_modulus<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<BigUint>, D::Error> {
+    let value = String::deserialize(deserializer)?;
+    parse_range_modulus(&value)
+        .map(Some)
+        .map_err(D::Error::custom)
+}
+
+// Reads the `program_input_ns` list, whose entries are `prefix=path` strings
+// like the values of --program_input_ns.
+fn deserialize_input_namespaces<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<Vec<(String, PathBuf)>>, D::Error> {
+    Vec::<String>::deserialize(deserializer)?
+        .iter()
+        .map(|value| parse_input_namespace(value))
+        .collect::<Result<_, _>>()
+        .map(Some)
+        .map_err(D::Error::custom)
+}
+
+// Returns the CLI defaults overridden by the settings in `config`. The program
+// file name is only a placeholder.
+pub(crate) fn args_from_config(config: Config) -> Result<Args, Error> {
+    let matches = Args::command().try_get_matches_from(["juvix-cairo-vm", "-"])?;
+    let mut args = Args::from_arg_matches(&matches)?;
+    config.apply(&mut args, &matches);
+    Ok(args)
+}
+
+// Parses the command line, using the `--config` file (if any) for the
+// arguments not given explicitly.
+pub fn parse_args(args: impl Iterator<Item = String>) -> Result<Args, Error> {
+    let matches = Args::command().try_get_matches_from(args)?;
+    let mut args = Args::from_arg_matches(&matches)?;
+    if let Some(ref path) = args.config {
+        let config = Config::from_toml(std::fs::read_to_string(path)?.as_str())?;
+        config.apply(&mut args, &matches);
+    }
+    Ok(args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_matches::assert_matches;
+
+    #[test]
+    fn test_config_defaults() {
+        let args = [
+            "juvix-cairo-vm",
+            "tests/input1.json",
+            "--config",
+            "tests/config/small_start.toml",
+        ]
+        .into_iter()
+        .map(String::from);
+        let args = parse_args(args).unwrap();
+        assert_eq!(args.layout, "small");
+        assert_eq!(args.entrypoint, "start");
+        assert!(args.proof_mode);
+    }
+
+    #[test]
+    fn test_config_cli_wins() {
+        let args = [
+            "juvix-cairo-vm",
+            "tests/input1.json",
+            "--config",
+            "tests/config/small_start.toml",
+            "--layout",
+            "all_cairo",
+        ]
+        .into_iter()
+        .map(String::from);
+        let args = parse_args(args).unwrap();
+        assert_eq!(args.layout, "all_cairo");
+        assert_eq!(args.entrypoint, "start");
+    }
+
+    #[test]
+    fn test_config_range_modulus() {
+        let config = Config::from_toml("input_range_modulus = \"0x61\"").unwrap();
+        assert_eq!(config.input_range_modulus, Some(BigUint::from(97u32)));
+        let args = args_from_config(config).unwrap();
+        assert_eq!(args.input_range_modulus, Some(BigUint::from(97u32)));
+        assert_matches!(
+            Config::from_toml("input_range_modulus = \"1\""),
+            Err(Error::Config(_))
+        );
+    }
+
+    #[test]
+    fn test_config_input_sources() {
+        let config = Config::from_toml(
+            "program_input_base64 = \"eyJYIjogOX0=\"\n\
+             program_input_ns = [\"a=tests/input2_input.json\"]",
+        )
+        .unwrap();
+        let args = args_from_config(config).unwrap();
+        assert_eq!(args.program_input_base64.as_deref(), Some("eyJYIjogOX0="));
+        assert_eq!(
+            args.program_input_ns,
+            vec![("a".to_string(), PathBuf::from("tests/input2_input.json"))]
+        );
+
+        // An input file on the command line replaces the encoded input
+        let matches = Args::command()
+            .try_get_matches_from([
+                "juvix-cairo-vm",
+                "-",
+                "--program_input",
+                "tests/input2_input.json",
+            ])
+            .unwrap();
+        let mut args = Args::from_arg_matches(&matches).unwrap();
+        Config::from_toml("program_input_base64 = \"eyJYIjogOX0=\"")
+            .unwrap()
+            .apply(&mut args, &matches);
+        assert_eq!(args.program_input_base64, None);
+
+        assert_matches!(
+            Config::from_toml("program_input_ns = [\"1a=tests/input2_input.json\"]"),
+            Err(Error::Config(_))
+        );
+        assert_matches!(
+            Config::from_toml(
+                "program_input = \"tests/input2_input.json\"\n\
+                 program_input_base64 = \"eyJYIjogOX0=\""
+            ),
+            Err(Error::Config(_))
+        );
+    }
+
+    #[test]
+    fn test_config_invalid() {
+        assert_matches!(
+            Config::from_toml("layout = \"broken\""),
+            Err(Error::Config(_))
+        );
+        assert_matches!(Config::from_toml("unknown = 1"), Err(Error::Config(_)));
+    }
+}
@@ -0,0 +1,16 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+// Set when SIGINT is received after `install_handler`, until the next call
+pub(crate) static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+// Replaces the default SIGINT behaviour, which kills the process on the spot,
+// with setting `INTERRUPTED`. The run then stops at the next step and the
+// files being written are completed, so no truncated artifact is left behind.
+// Called at the start of each run, so it also clears the flag: a SIGINT
+// received during an earlier run in the same process does not stop this one.
+pub(crate) fn install_handler() {
+    INTERRUPTED.store(false, Ordering::SeqCst);
+    // Fails only if a handler is already installed, e.g. by an earlier run in
+    // the same process, which sets the same flag.
+    let _ = ctrlc::set_handler(|| INTERRUPTED.store(true, Ordering::SeqCst));
+}
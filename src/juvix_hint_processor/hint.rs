@@ -1,6 +1,78 @@
 #[derive(Debug, Clone, PartialEq)]
 pub enum Hint {
     Input(String),
+    InputInto(String),
+    InputLen(String),
     Alloc(usize),
+    // Like Alloc, but the allocated cells are set to 0
+    AllocZero(usize),
     RandomEcPoint,
+    Keccak(String, String, String),
+    Poseidon(String, String, String),
+    Pedersen(String, String),
+    SignedLt(String, String),
+    PopCount(String),
+    PackBytes(String),
+    Concat(String, String),
+    // The number of steps executed so far. The count depends on how the
+    // program was compiled and run (e.g. proof mode adds steps), so it is
+    // not deterministic across configurations.
+    StepCount,
+    AssertInputEq(String, String),
+    InputIndex(String, String),
+    // Advisory only: the result is a nondeterministic guess which the
+    // program must check with on-circuit constraints.
+    RangeCheck(String, String),
+    MerkleRoot(MerkleHash, String, String),
+    Reverse(String),
+    // The number of cells of the encoded value the pointer refers to. Memory
+    // is untyped, so the value is taken to extend up to the first unwritten
+    // cell: this holds for the values written in a segment of their own,
+    // e.g. by Input.
+    EncodedLen(String),
+    // Writes the input `a` if the referenced condition is nonzero, else the
+    // input `b`, the same way as Input
+    Select(String, String, String),
+    // Writes the list input with the referenced delta added to each element
+    MapIncrement(String, String),
+    // Fails unless the list input has exactly the given number of elements
+    AssertLen(String, usize),
+}
+
+/// The hash combining two sibling nodes of a Merkle tree.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MerkleHash {
+    Poseidon,
+    Pedersen,
+}
+
+impl Hint {
+    // The name of the hint kind, as written in the hint code
+    pub fn name(&self) -> &'static str {
+        match self {
+            Hint::Input(_) => "Input",
+            Hint::InputInto(_) => "InputInto",
+            Hint::InputLen(_) => "InputLen",
+            Hint::Alloc(_) => "Alloc",
+            Hint::AllocZero(_) => "AllocZero",
+            Hint::RandomEcPoint => "RandomEcPoint",
+            Hint::Keccak(..) => "Keccak",
+            Hint::Poseidon(..) => "Poseidon",
+            Hint::Pedersen(..) => "Pedersen",
+            Hint::SignedLt(..) => "SignedLt",
+            Hint::PopCount(_) => "PopCount",
+            Hint::PackBytes(_) => "PackBytes",
+            Hint::Concat(..) => "Concat",
+            Hint::StepCount => "StepCount",
+            Hint::AssertInputEq(..) => "AssertInputEq",
+            Hint::InputIndex(..) => "InputIndex",
+            Hint::RangeCheck(..) => "RangeCheck",
+            Hint::MerkleRoot(..) => "MerkleRoot",
+            Hint::Reverse(_) => "Reverse",
+            Hint::EncodedLen(_) => "EncodedLen",
+            Hint::Select(..) => "Select",
+            Hint::MapIncrement(..) => "MapIncrement",
+            Hint::AssertLen(..) => "AssertLen",
+        }
+    }
 }
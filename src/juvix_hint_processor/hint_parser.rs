@@ -1,13 +1,13 @@
 use std::str::FromStr;
 
-use super::hint::Hint;
+use super::hint::{Hint, MerkleHash};
 use nom::{
     branch::alt,
     bytes::complete::tag,
     character::complete::{alpha1, alphanumeric1, char, multispace0, u64 as parse_u64},
     combinator::{all_consuming, map, recognize},
     multi::many0,
-    sequence::{delimited, pair, preceded, tuple},
+    sequence::{delimited, pair, preceded, terminated, tuple},
     IResult,
 };
 
@@ -15,12 +15,20 @@ fn parse_usize(input: &str) -> IResult<&str, usize> {
     map(parse_u64, |num: u64| num as usize)(input)
 }
 
+// Parses an identifier, or a dotted path of identifiers such as `ns.x` naming
+// a variable of a --program_input_ns input file.
 fn parse_identifier(input: &str) -> IResult<&str, String> {
-    recognize(pair(
-        alt((alpha1, tag("_"))),
-        many0(alt((alphanumeric1, tag("_")))),
-    ))(input)
-    .map(|(x, y)| (x, y.to_string()))
+    fn part(input: &str) -> IResult<&str, &str> {
+        recognize(pair(
+            alt((alpha1, tag("_"))),
+            many0(alt((alphanumeric1, tag("_")))),
+        ))(input)
+    }
+    recognize(pair(part, many0(pair(char('.'), part))))(input).map(|(x, y)| (x, y.to_string()))
+}
+
+fn parse_comma(input: &str) -> IResult<&str, char> {
+    delimited(multispace0, char(','), multispace0)(input)
 }
 
 fn parse_input(input: &str) -> IResult<&str, Hint> {
@@ -37,6 +45,90 @@ fn parse_input(input: &str) -> IResult<&str, Hint> {
     )(input)
 }
 
+fn parse_input_into(input: &str) -> IResult<&str, Hint> {
+    map(
+        preceded(
+            tuple((tag("InputInto"), multispace0, char('('), multispace0)),
+            delimited(
+                multispace0,
+                parse_identifier,
+                tuple((multispace0, char(')'))),
+            ),
+        ),
+        Hint::InputInto,
+    )(input)
+}
+
+fn parse_input_len(input: &str) -> IResult<&str, Hint> {
+    map(
+        preceded(
+            tuple((tag("InputLen"), multispace0, char('('), multispace0)),
+            delimited(
+                multispace0,
+                parse_identifier,
+                tuple((multispace0, char(')'))),
+            ),
+        ),
+        Hint::InputLen,
+    )(input)
+}
+
+fn parse_pop_count(input: &str) -> IResult<&str, Hint> {
+    map(
+        preceded(
+            tuple((tag("PopCount"), multispace0, char('('))),
+            delimited(
+                multispace0,
+                parse_identifier,
+                tuple((multispace0, char(')'))),
+            ),
+        ),
+        Hint::PopCount,
+    )(input)
+}
+
+fn parse_pack_bytes(input: &str) -> IResult<&str, Hint> {
+    map(
+        preceded(
+            tuple((tag("PackBytes"), multispace0, char('('))),
+            delimited(
+                multispace0,
+                parse_identifier,
+                tuple((multispace0, char(')'))),
+            ),
+        ),
+        Hint::PackBytes,
+    )(input)
+}
+
+fn parse_reverse(input: &str) -> IResult<&str, Hint> {
+    map(
+        preceded(
+            tuple((tag("Reverse"), multispace0, char('('))),
+            delimited(
+                multispace0,
+                parse_identifier,
+                tuple((multispace0, char(')'))),
+            ),
+        ),
+        Hint::Reverse,
+    )(input)
+}
+
+fn parse_encoded_len(input: &str) -> IResult<&str, Hint> {
+    map(
+        preceded(
+            tuple((tag("EncodedLen"), multispace0, char('('))),
+            delimited(
+                multispace0,
+                parse_identifier,
+                tuple((multispace0, char(')'))),
+            ),
+        ),
+        Hint::EncodedLen,
+    )(input)
+}
+
 fn parse_alloc(input: &str) -> IResult<&str, Hint> {
     map(
         preceded(
@@ -47,14 +139,176 @@ fn parse_alloc(input: &str) -> IResult<&str, Hint> {
     )(input)
 }
 
+fn parse_alloc_zero(input: &str) -> IResult<&str, Hint> {
+    map(
+        preceded(
+            tuple((tag("AllocZero"), multispace0, char('('))),
+            delimited(multispace0, parse_usize, tuple((multispace0, char(')')))),
+        ),
+        Hint::AllocZero,
+    )(input)
+}
+
 fn parse_random_ec_point(input: &str) -> IResult<&str, Hint> {
     map(tag("RandomEcPoint"), |_| Hint::RandomEcPoint)(input)
 }
 
+fn parse_step_count(input: &str) -> IResult<&str, Hint> {
+    map(tag("StepCount"), |_| Hint::StepCount)(input)
+}
+
+// Parses `name(a, b, c)`, the common shape of the hashing hints
+// (`name(input_ptr, len, out)`) and of Select.
+fn parse_ternary<'a>(
+    name: &'static str,
+    hint: fn(String, String, String) -> Hint,
+) -> impl FnMut(&'a str) -> IResult<&'a str, Hint> {
+    map(
+        preceded(
+            tuple((tag(name), multispace0, char('('), multispace0)),
+            terminated(
+                tuple((
+                    parse_identifier,
+                    parse_comma,
+                    parse_identifier,
+                    parse_comma,
+                    parse_identifier,
+                )),
+                tuple((multispace0, char(')'))),
+            ),
+        ),
+        move |(a, _, b, _, c)| hint(a, b, c),
+    )
+}
+
+fn parse_keccak(input: &str) -> IResult<&str, Hint> {
+    parse_ternary("Keccak", Hint::Keccak)(input)
+}
+
+fn parse_poseidon(input: &str) -> IResult<&str, Hint> {
+    parse_ternary("Poseidon", Hint::Poseidon)(input)
+}
+
+// Parses `name(a, b)`, the common shape of the hints over two felts.
+fn parse_binary<'a>(
+    name: &'static str,
+    hint: fn(String, String) -> Hint,
+) -> impl FnMut(&'a str) -> IResult<&'a str, Hint> {
+    map(
+        preceded(
+            tuple((tag(name), multispace0, char('('), multispace0)),
+            terminated(
+                tuple((parse_identifier, parse_comma, parse_identifier)),
+                tuple((multispace0, char(')'))),
+            ),
+        ),
+        move |(a, _, b)| hint(a, b),
+    )
+}
+
+fn parse_pedersen(input: &str) -> IResult<&str, Hint> {
+    parse_binary("Pedersen", Hint::Pedersen)(input)
+}
+
+fn parse_signed_lt(input: &str) -> IResult<&str, Hint> {
+    parse_binary("SignedLt", Hint::SignedLt)(input)
+}
+
+fn parse_concat(input: &str) -> IResult<&str, Hint> {
+    parse_binary("Concat", Hint::Concat)(input)
+}
+
+fn parse_select(input: &str) -> IResult<&str, Hint> {
+    parse_ternary("Select", Hint::Select)(input)
+}
+
+fn parse_assert_input_eq(input: &str) -> IResult<&str, Hint> {
+    parse_binary("AssertInputEq", Hint::AssertInputEq)(input)
+}
+
+fn parse_input_index(input: &str) -> IResult<&str, Hint> {
+    parse_binary("InputIndex", Hint::InputIndex)(input)
+}
+
+fn parse_map_increment(input: &str) -> IResult<&str, Hint> {
+    parse_binary("MapIncrement", Hint::MapIncrement)(input)
+}
+
+fn parse_assert_len(input: &str) -> IResult<&str, Hint> {
+    map(
+        preceded(
+            tuple((tag("AssertLen"), multispace0, char('('), multispace0)),
+            terminated(
+                tuple((parse_identifier, parse_comma, parse_usize)),
+                tuple((multispace0, char(')'))),
+            ),
+        ),
+        |(var, _, len)| Hint::AssertLen(var, len),
+    )(input)
+}
+
+fn parse_range_check(input: &str) -> IResult<&str, Hint> {
+    parse_binary("RangeCheck", Hint::RangeCheck)(input)
+}
+
+fn parse_merkle_hash(input: &str) -> IResult<&str, MerkleHash> {
+    alt((
+        map(tag("Poseidon"), |_| MerkleHash::Poseidon),
+        map(tag("Pedersen"), |_| MerkleHash::Pedersen),
+    ))(input)
+}
+
+// Parses `MerkleRoot(hash, leaves_ptr, len)`.
+fn parse_merkle_root(input: &str) -> IResult<&str, Hint> {
+    map(
+        preceded(
+            tuple((tag("MerkleRoot"), multispace0, char('('), multispace0)),
+            terminated(
+                tuple((
+                    parse_merkle_hash,
+                    parse_comma,
+                    parse_identifier,
+                    parse_comma,
+                    parse_identifier,
+                )),
+                tuple((multispace0, char(')'))),
+            ),
+        ),
+        |(hash, _, leaves_ptr, _, len)| Hint::MerkleRoot(hash, leaves_ptr, len),
+    )(input)
+}
+
 fn parse_hint(input: &str) -> IResult<&str, Hint> {
     all_consuming(delimited(
         multispace0,
-        alt((parse_input, parse_alloc, parse_random_ec_point)),
+        // alt takes at most 21 parsers
+        alt((
+            alt((
+                parse_input_into,
+                parse_input_len,
+                parse_input_index,
+                parse_input,
+                parse_select,
+                parse_map_increment,
+                parse_alloc_zero,
+                parse_alloc,
+            )),
+            parse_random_ec_point,
+            parse_keccak,
+            parse_poseidon,
+            parse_pedersen,
+            parse_signed_lt,
+            parse_pop_count,
+            parse_pack_bytes,
+            parse_concat,
+            parse_step_count,
+            parse_assert_input_eq,
+            parse_assert_len,
+            parse_range_check,
+            parse_merkle_root,
+            parse_reverse,
+            parse_encoded_len,
+        )),
         multispace0,
     ))(input)
 }
@@ -99,9 +353,79 @@ mod tests {
             Hint::Input(String::from("ident_"))))]
     #[case((r#"Input(__ident_)"#,
             Hint::Input(String::from("__ident_"))))]
+    #[case((r#"Input(ns.ident)"#,
+            Hint::Input(String::from("ns.ident"))))]
+    #[case((r#"Input(a.b_1.c)"#,
+            Hint::Input(String::from("a.b_1.c"))))]
+    #[case((r#"InputInto(variable)"#,
+            Hint::InputInto(String::from("variable"))))]
+    #[case((r#" InputInto ( variable ) "#,
+            Hint::InputInto(String::from("variable"))))]
+    #[case((r#"InputLen(xs)"#,
+            Hint::InputLen(String::from("xs"))))]
+    #[case((r#" InputLen ( xs ) "#,
+            Hint::InputLen(String::from("xs"))))]
     #[case((r#"Alloc(123)"#, Hint::Alloc(123)))]
+    #[case((r#"Alloc(0)"#, Hint::Alloc(0)))]
     #[case((r#" Alloc ( 123 ) "#, Hint::Alloc(123)))]
+    #[case((r#"AllocZero(8)"#, Hint::AllocZero(8)))]
+    #[case((r#" AllocZero ( 8 ) "#, Hint::AllocZero(8)))]
     #[case((r#" RandomEcPoint  "#, Hint::RandomEcPoint))]
+    #[case((r#"StepCount"#, Hint::StepCount))]
+    #[case((r#" StepCount "#, Hint::StepCount))]
+    #[case((r#"Keccak(ptr, len, out)"#,
+            Hint::Keccak(String::from("ptr"), String::from("len"), String::from("out"))))]
+    #[case((r#"Poseidon(ptr, len, out)"#,
+            Hint::Poseidon(String::from("ptr"), String::from("len"), String::from("out"))))]
+    #[case((r#"Pedersen(a, b)"#,
+            Hint::Pedersen(String::from("a"), String::from("b"))))]
+    #[case((r#" Pedersen( a ,b ) "#,
+            Hint::Pedersen(String::from("a"), String::from("b"))))]
+    #[case((r#"SignedLt(x, y)"#,
+            Hint::SignedLt(String::from("x"), String::from("y"))))]
+    #[case((r#"PopCount(x)"#, Hint::PopCount(String::from("x"))))]
+    #[case((r#" PopCount ( x ) "#, Hint::PopCount(String::from("x"))))]
+    #[case((r#"PackBytes(bytes)"#, Hint::PackBytes(String::from("bytes"))))]
+    #[case((r#"Reverse(xs)"#, Hint::Reverse(String::from("xs"))))]
+    #[case((r#" Reverse ( xs ) "#, Hint::Reverse(String::from("xs"))))]
+    #[case((r#"EncodedLen(ptr)"#, Hint::EncodedLen(String::from("ptr"))))]
+    #[case((
+        r#"MapIncrement(xs, d)"#,
+        Hint::MapIncrement(String::from("xs"), String::from("d"))
+    ))]
+    #[case((
+        r#"Select(c, a, b)"#,
+        Hint::Select(String::from("c"), String::from("a"), String::from("b"))
+    ))]
+    #[case((
+        r#" Select ( c , ns.a , b ) "#,
+        Hint::Select(String::from("c"), String::from("ns.a"), String::from("b"))
+    ))]
+    #[case((r#"Concat(xs, ys)"#,
+            Hint::Concat(String::from("xs"), String::from("ys"))))]
+    #[case((r#" Concat ( xs,ys ) "#,
+            Hint::Concat(String::from("xs"), String::from("ys"))))]
+    #[case((r#"MerkleRoot(Poseidon, leaves, len)"#,
+            Hint::MerkleRoot(MerkleHash::Poseidon, String::from("leaves"), String::from("len"))))]
+    #[case((r#" MerkleRoot ( Pedersen,leaves , len ) "#,
+            Hint::MerkleRoot(MerkleHash::Pedersen, String::from("leaves"), String::from("len"))))]
+    #[case((r#"InputIndex(xs, i)"#,
+            Hint::InputIndex(String::from("xs"), String::from("i"))))]
+    #[case((r#" InputIndex ( xs ,i ) "#,
+            Hint::InputIndex(String::from("xs"), String::from("i"))))]
+    #[case((r#"RangeCheck(x, bound)"#,
+            Hint::RangeCheck(String::from("x"), String::from("bound"))))]
+    #[case((r#" RangeCheck ( x,bound ) "#,
+            Hint::RangeCheck(String::from("x"), String::from("bound"))))]
+    #[case((r#"AssertInputEq(a, b)"#,
+            Hint::AssertInputEq(String::from("a"), String::from("b"))))]
+    #[case((r#" AssertInputEq ( a,b ) "#,
+            Hint::AssertInputEq(String::from("a"), String::from("b"))))]
+    #[case((r#"AssertLen(xs, 3)"#, Hint::AssertLen(String::from("xs"), 3)))]
+    #[case((r#" AssertLen ( ns.xs,0 ) "#, Hint::AssertLen(String::from("ns.xs"), 0)))]
+    #[case((r#" PackBytes ( bytes ) "#, Hint::PackBytes(String::from("bytes"))))]
+    #[case((r#" Keccak ( ptr ,len,  out ) "#,
+            Hint::Keccak(String::from("ptr"), String::from("len"), String::from("out"))))]
     fn tests_positive(#[case] arg: (&str, Hint)) {
         assert_eq!(arg.0.parse::<Hint>().unwrap(), arg.1)
     }
@@ -111,10 +435,57 @@ mod tests {
     #[case("Incomplete")]
     #[case("Alloc(34) extra")]
     #[case("Alloc(-1)")]
+    #[case("AllocZero()")]
+    #[case("AllocZero(n)")]
     #[case("Input(var) extra")]
     #[case("Input(1var)")]
     #[case("Input(var var)")]
+    #[case("Input(ns.)")]
+    #[case("Input(.var)")]
+    #[case("Input(ns..var)")]
+    #[case("Input(ns.1var)")]
+    #[case("InputInto(1var)")]
+    #[case("InputInto()")]
+    #[case("InputLen()")]
+    #[case("InputLen(xs, ys)")]
     #[case("RandomEcPoint()")]
+    #[case("StepCount()")]
+    #[case("StepCount(x)")]
+    #[case("Keccak(ptr, len)")]
+    #[case("Keccak(ptr, len, out, extra)")]
+    #[case("Keccak(ptr len out)")]
+    #[case("Poseidon(ptr, len)")]
+    #[case("Poseidon(ptr, 1, out)")]
+    #[case("Pedersen(a)")]
+    #[case("Pedersen(a, b, c)")]
+    #[case("SignedLt(x)")]
+    #[case("SignedLt(x, 1)")]
+    #[case("PopCount(1)")]
+    #[case("PopCount(x, y)")]
+    #[case("PackBytes()")]
+    #[case("Concat(xs)")]
+    #[case("Concat(xs, ys, zs)")]
+    #[case("PackBytes(1)")]
+    #[case("Reverse()")]
+    #[case("Reverse(xs, ys)")]
+    #[case("EncodedLen()")]
+    #[case("EncodedLen(1)")]
+    #[case("Select(c, a)")]
+    #[case("MapIncrement(xs)")]
+    #[case("MapIncrement(xs, 1)")]
+    #[case("Select(c, a, b, d)")]
+    #[case("AssertInputEq(a)")]
+    #[case("RangeCheck(x)")]
+    #[case("RangeCheck(x, 10)")]
+    #[case("InputIndex(xs)")]
+    #[case("MerkleRoot(Keccak, leaves, len)")]
+    #[case("MerkleRoot(leaves, len)")]
+    #[case("InputIndex(xs, 1)")]
+    #[case("AssertInputEq(a, 1)")]
+    #[case("AssertLen(xs)")]
+    #[case("AssertLen(xs, n)")]
+    #[case("AssertLen(xs, -1)")]
+    #[case("AssertLen(3, xs)")]
     fn tests_negative(#[case] arg: &str) {
         match arg.parse::<Hint>() {
             Ok(_) => assert!(false),
@@ -1,9 +1,17 @@
 use ark_ff::fields::{Fp256, MontBackend, MontConfig};
 use ark_ff::{Field, PrimeField};
+use ark_std::rand::rngs::StdRng;
+use ark_std::rand::{RngCore, SeedableRng};
 use ark_std::UniformRand;
 use cairo_vm::any_box;
+use cairo_vm::hint_processor::builtin_hint_processor::builtin_hint_processor_definition::BuiltinHintProcessor;
+use cairo_vm::hint_processor::builtin_hint_processor::hint_utils::{
+    get_integer_from_var_name, get_ptr_from_var_name,
+};
 use cairo_vm::hint_processor::hint_processor_definition::HintReference;
-use cairo_vm::types::relocatable::Relocatable;
+use cairo_vm::hint_processor::hint_processor_utils::felt_to_usize;
+use cairo_vm::serde::deserialize_program::ApTracking;
+use cairo_vm::types::relocatable::{MaybeRelocatable, Relocatable};
 use cairo_vm::vm::runners::cairo_runner::ResourceTracker;
 use cairo_vm::vm::runners::cairo_runner::RunResources;
 use cairo_vm::Felt252;
@@ -14,12 +22,18 @@ use cairo_vm::{
     vm::{errors::hint_errors::HintError, vm_core::VirtualMachine},
 };
 use indexmap::IndexMap;
-use num_bigint::BigUint;
+use num_bigint::{BigInt, BigUint};
+use sha3::{Digest, Keccak256};
+use starknet_crypto::{pedersen_hash, poseidon_hash, poseidon_hash_many, FieldElement};
 use std::any::Any;
+use std::borrow::Cow;
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
 
-use super::hint::Hint;
+use super::hint::{Hint, MerkleHash};
 use crate::program_input::{ProgramInput, Value};
+use crate::progress::Progress;
+use crate::warning;
 
 #[derive(MontConfig)]
 #[modulus = "3618502788666131213697322783095070105623107215331596699973092056135872020481"]
@@ -42,30 +56,350 @@ fn get_cid(n: usize) -> usize {
     n * 2 + 1
 }
 
+/// Interprets a field element as a signed integer: values above half of the
+/// modulus stand for the negative numbers `felt - modulus`.
+pub(crate) fn felt_to_signed(felt: &Felt252) -> BigInt {
+    let modulus = Felt252::MAX.to_biguint() + 1u32;
+    let value = felt.to_biguint();
+    if value > &modulus / 2u32 {
+        BigInt::from(value) - BigInt::from(modulus)
+    } else {
+        BigInt::from(value)
+    }
+}
+
+// Writes the length of `bytes` followed by the bytes packed into felts,
+// `STRING_CHUNK_SIZE` bytes per felt in big-endian order, and returns the
+// number of memory words written.
+fn write_packed_bytes(
+    vm: &mut VirtualMachine,
+    addr: Relocatable,
+    bytes: &[u8],
+) -> Result<usize, HintError> {
+    vm.insert_value(addr, bytes.len())
+        .map_err(HintError::Memory)?;
+    let mut addr1 = (addr + 1_usize).map_err(HintError::Math)?;
+    for chunk in bytes.chunks(STRING_CHUNK_SIZE) {
+        vm.insert_value(addr1, Felt252::from_bytes_be_slice(chunk))
+            .map_err(HintError::Memory)?;
+        addr1 = (addr1 + 1_usize).map_err(HintError::Math)?;
+    }
+    Ok((addr1 - addr)?)
+}
+
+fn felt_to_field_element(felt: &Felt252) -> Result<FieldElement, HintError> {
+    FieldElement::from_bytes_be(&felt.to_bytes_be())
+        .map_err(|_| HintError::CustomHint("Invalid field element".to_string().into_boxed_str()))
+}
+
+/// Returns the number of memory cells written by `read_value_input` for `val`.
+fn encoded_size(val: &Value) -> usize {
+    // the number of cells a pointer field refers to
+    fn pointee_size(val: &Value) -> usize {
+        match val {
+            Value::ValueFelt(_) | Value::ValueBool(_) => 0,
+            _ => encoded_size(val),
+        }
+    }
+    match val {
+        Value::ValueFelt(_) | Value::ValueBool(_) => 1,
+        Value::ValueRecord(fields) => fields.values().map(|v| 1 + pointee_size(v)).sum(),
+        Value::ValueList(elems) => elems.iter().map(|v| 3 + pointee_size(v)).sum::<usize>() + 1,
+        Value::ValueString(s) => 1 + s.len().div_ceil(STRING_CHUNK_SIZE),
+    }
+}
+
+/// Returns the address past the value encoded at `addr` the way
+/// `read_value_input` encodes a value behind a pointer. The encoding carries
+/// no types, so the value is read as:
+/// - a list when it starts with a cons cell `[cid(1), value, next]` or the
+///   nil cell `cid(0)`, following the `next` pointers up to the nil cell. An
+///   element behind a pointer directly follows its cons cell and ends where
+///   the next cons cell starts;
+/// - a record when a pointer ahead in the segment is found among the cells a
+///   string would have: the fields extend up to the first nested value, each
+///   nested value ends where the next one starts, and the last one is walked;
+/// - a string otherwise: its length in bytes followed by the packed bytes.
+///
+/// A record made of felts only reads the same as a string, so it is measured
+/// as one when it is not followed by another value of its parent. The value
+/// is only bounded by its own structure, so the cells written after it do not
+/// count.
+fn encoded_value_end(vm: &VirtualMachine, addr: Relocatable) -> Result<Relocatable, String> {
+    let cell = |addr: Relocatable| {
+        vm.get_maybe(&addr)
+            .ok_or_else(|| format!("the cell {addr} is unwritten"))
+    };
+    // the nested value a cell refers to: the ones written by the encoder lie
+    // ahead in the same segment
+    let nested = |field: Relocatable| match vm.get_maybe(&field) {
+        Some(MaybeRelocatable::RelocatableValue(target))
+            if target.segment_index == field.segment_index && target > field =>
+        {
+            Some(target)
+        }
+        _ => None,
+    };
+    let is_header = |cell: &MaybeRelocatable, n: usize| {
+        *cell == MaybeRelocatable::from(Felt252::from(get_cid(n)))
+    };
+    let add = |addr: Relocatable, n: usize| (addr + n).map_err(|e| e.to_string());
+
+    let first = cell(addr)?;
+    if is_header(&first, 0) || (is_header(&first, 1) && nested(add(addr, 2)?).is_some()) {
+        let mut cons = addr;
+        loop {
+            let header = cell(cons)?;
+            if is_header(&header, 0) {
+                return add(cons, 1);
+            }
+            if !is_header(&header, 1) {
+                return Err(format!("{cons} is neither a cons nor a nil cell"));
+            }
+            let next = nested(add(cons, 2)?)
+                .ok_or_else(|| format!("the cons cell {cons} has no next pointer"))?;
+            match nested(add(cons, 1)?) {
+                Some(value) if value != add(cons, 3)? => {
+                    return Err(format!(
+                        "the list element at {value} does not follow its cons cell {cons}"
+                    ))
+                }
+                None if next != add(cons, 3)? => {
+                    return Err(format!(
+                        "the cons cell {cons} is not followed by the next one, at {next}"
+                    ))
+                }
+                _ => (),
+            }
+            cons = next;
+        }
+    }
+
+    let string_size = match first {
+        MaybeRelocatable::Int(len) => felt_to_usize(&len)
+            .ok()
+            .map(|len| 1 + len.div_ceil(STRING_CHUNK_SIZE)),
+        MaybeRelocatable::RelocatableValue(_) => None,
+    };
+    let mut fields = 0;
+    let first_nested = loop {
+        if string_size == Some(fields) {
+            break None;
+        }
+        let field = add(addr, fields)?;
+        cell(field)?;
+        if let Some(target) = nested(field) {
+            break Some(target);
+        }
+        fields += 1;
+    };
+    let Some(first_nested) = first_nested else {
+        return add(addr, fields);
+    };
+    // the record fields are the cells before its first nested value
+    let mut last = None;
+    for i in 0..(first_nested - addr).map_err(|e| e.to_string())? {
+        if let Some(value) = nested(add(addr, i)?) {
+            if let Some(previous) = last.filter(|previous| value <= *previous) {
+                return Err(format!(
+                    "the nested value at {value} does not follow the previous one, at {previous}"
+                ));
+            }
+            last = Some(value);
+        }
+    }
+    encoded_value_end(vm, last.unwrap_or(first_nested))
+}
+
+/// Appends the cells written by `read_value_input` for `val` to `out`, with
+/// the pointers given as offsets into `out`.
+pub(crate) fn flatten_value(val: &Value, out: &mut Vec<Felt252>) {
+    match val {
+        Value::ValueFelt(v) => out.push(*v),
+        Value::ValueBool(v) => out.push(Felt252::from(if *v { 0 } else { 1 })),
+        Value::ValueRecord(fields) => {
+            let base = out.len();
+            out.resize(base + fields.len(), Felt252::ZERO);
+            for (i, field) in fields.values().enumerate() {
+                out[base + i] = flatten_field(field, out);
+            }
+        }
+        Value::ValueList(elems) => {
+            for elem in elems {
+                let cell = out.len();
+                out.extend([Felt252::from(get_cid(1)), Felt252::ZERO, Felt252::ZERO]);
+                out[cell + 1] = flatten_field(elem, out);
+                out[cell + 2] = Felt252::from(out.len());
+            }
+            out.push(Felt252::from(get_cid(0)));
+        }
+        Value::ValueString(s) => {
+            out.push(Felt252::from(s.len()));
+            out.extend(
+                s.as_bytes()
+                    .chunks(STRING_CHUNK_SIZE)
+                    .map(Felt252::from_bytes_be_slice),
+            );
+        }
+    }
+}
+
+/// Returns the cell of a record field or list element: felts and booleans
+/// are stored inline, the other values are appended to `out` and referred to
+/// by their offset.
+fn flatten_field(val: &Value, out: &mut Vec<Felt252>) -> Felt252 {
+    match val {
+        Value::ValueFelt(v) => *v,
+        Value::ValueBool(v) => Felt252::from(if *v { 0 } else { 1 }),
+        _ => {
+            let offset = out.len();
+            flatten_value(val, out);
+            Felt252::from(offset)
+        }
+    }
+}
+
+/// The number of bytes packed into a single felt when encoding strings.
+const STRING_CHUNK_SIZE: usize = 31;
+
 /// Execution scope for constant memory allocation.
 struct MemoryExecScope {
     /// The first free address in the segment.
     next_address: Relocatable,
+    /// The sizes of the blocks allocated so far, by their base address.
+    allocated: HashMap<Relocatable, usize>,
+}
+
+/// Hint data produced by `compile_hint`: the parsed hint together with the
+/// references (`ids`) visible at the hint location.
+struct JuvixHintData {
+    hint: Hint,
+    ids_data: HashMap<String, HintReference>,
+    ap_tracking: ApTracking,
 }
 
-pub struct JuvixHintProcessor {
-    program_input: ProgramInput,
+/// Maps the (unqualified) names of the references visible at a hint location
+/// to their definitions.
+fn get_ids_data(
+    reference_ids: &HashMap<String, usize>,
+    references: &[HintReference],
+) -> Result<HashMap<String, HintReference>, VirtualMachineError> {
+    reference_ids
+        .iter()
+        .map(|(path, ref_id)| {
+            let name = path
+                .rsplit('.')
+                .next()
+                .ok_or(VirtualMachineError::Unexpected)?;
+            let reference = references
+                .get(*ref_id)
+                .ok_or(VirtualMachineError::Unexpected)?;
+            Ok((name.to_string(), reference.clone()))
+        })
+        .collect()
+}
+
+pub struct JuvixHintProcessor<'a> {
+    program_input: Cow<'a, ProgramInput>,
     run_resources: RunResources,
+    // Handles the hints which are not Juvix hints, if enabled
+    builtin_hint_processor: Option<BuiltinHintProcessor>,
+    // Reject `Alloc(0)` instead of only warning about it
+    strict_alloc: bool,
+    // The number of steps executed in the current run
+    steps: usize,
+    // Stop the run once this flag is set
+    interrupt: Option<&'static AtomicBool>,
+    // The seed of the RNG drawing random EC points, instead of the fixed one
+    seed: Option<u64>,
+    // Shows the steps consumed on stderr
+    progress: Option<Progress>,
 }
 
-impl JuvixHintProcessor {
+impl<'a> JuvixHintProcessor<'a> {
     pub fn new(program_input: ProgramInput) -> Self {
+        Self::with_input(Cow::Owned(program_input))
+    }
+
+    // Like new, but reads the input from `program_input` without taking it,
+    // so that several runs can share it.
+    pub fn borrowed(program_input: &'a ProgramInput) -> Self {
+        Self::with_input(Cow::Borrowed(program_input))
+    }
+
+    fn with_input(program_input: Cow<'a, ProgramInput>) -> Self {
         Self {
             program_input,
             run_resources: RunResources::default(),
+            builtin_hint_processor: None,
+            strict_alloc: false,
+            steps: 0,
+            interrupt: None,
+            seed: None,
+            progress: None,
+        }
+    }
+
+    // Delegates the hints which are not Juvix hints to the standard cairo-vm
+    // hint processor instead of rejecting them.
+    pub fn with_builtin_hints(mut self) -> Self {
+        self.builtin_hint_processor = Some(BuiltinHintProcessor::new_empty());
+        self
+    }
+
+    // Makes `Alloc(0)`, which usually indicates a compiler bug, fail instead
+    // of only printing a warning.
+    pub fn with_strict_alloc(mut self) -> Self {
+        self.strict_alloc = true;
+        self
+    }
+
+    // Stops the run at the next step once `flag` is set.
+    pub fn with_interrupt(mut self, flag: &'static AtomicBool) -> Self {
+        self.interrupt = Some(flag);
+        self
+    }
+
+    // Draws the random EC points from an RNG seeded with `seed` instead of the
+    // fixed seed.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    // Shows a progress indicator on stderr as the steps are consumed, unless
+    // stderr is not a terminal.
+    pub fn with_progress(mut self) -> Self {
+        self.progress = Progress::new();
+        self
+    }
+
+    // Returns true if the run was stopped by the interrupt flag.
+    pub fn interrupted(&self) -> bool {
+        self.interrupt
+            .is_some_and(|flag| flag.load(Ordering::SeqCst))
+    }
+
+    // Prepares the processor for another run of the program. The random EC
+    // point hint draws from a freshly seeded RNG on each call, so the only
+    // state to restore is the resource budget and the step count.
+    pub fn reset(&mut self) {
+        self.run_resources = RunResources::default();
+        self.steps = 0;
+        if let Some(ref mut builtin_hint_processor) = self.builtin_hint_processor {
+            *builtin_hint_processor = BuiltinHintProcessor::new_empty();
         }
     }
+
     // Runs a single Hint
     pub fn execute(
         &self,
         vm: &mut VirtualMachine,
         exec_scopes: &mut ExecutionScopes,
         hint: &Hint,
+        ids_data: &HashMap<String, HintReference>,
+        ap_tracking: &ApTracking,
+        constants: &HashMap<String, Felt252>,
     ) -> Result<(), HintError> {
         match hint {
             Hint::Alloc(size) => {
@@ -73,9 +407,84 @@ impl JuvixHintProcessor {
                 Ok(())
             }
 
-            Hint::Input(var) => self.read_program_input(vm, var),
+            Hint::AllocZero(size) => {
+                let addr = self.alloc_constant_size(vm, exec_scopes, *size)?;
+                for i in 0..*size {
+                    vm.insert_value((addr + i)?, Felt252::ZERO)?;
+                }
+                Ok(())
+            }
+
+            Hint::Input(var) => self.read_program_input(vm, var, constants),
+
+            Hint::InputInto(var) => self.read_program_input_into(vm, exec_scopes, var),
+
+            Hint::InputLen(var) => self.read_input_len(vm, var),
+
+            Hint::InputIndex(var, index) => {
+                self.read_input_index(vm, var, index, ids_data, ap_tracking)
+            }
 
             Hint::RandomEcPoint => self.random_ec_point(vm, exec_scopes),
+
+            Hint::Keccak(input_ptr, len, out) => {
+                self.keccak(vm, input_ptr, len, out, ids_data, ap_tracking)
+            }
+
+            Hint::Poseidon(input_ptr, len, out) => {
+                self.poseidon(vm, input_ptr, len, out, ids_data, ap_tracking)
+            }
+
+            Hint::Pedersen(a, b) => self.pedersen(vm, a, b, ids_data, ap_tracking),
+
+            Hint::SignedLt(a, b) => self.signed_lt(vm, a, b, ids_data, ap_tracking),
+
+            Hint::RangeCheck(var, bound) => self.range_check(vm, var, bound, ids_data, ap_tracking),
+
+            Hint::PopCount(var) => self.pop_count(vm, var, ids_data, ap_tracking),
+
+            Hint::MerkleRoot(hash, leaves_ptr, len) => {
+                self.merkle_root(vm, *hash, leaves_ptr, len, ids_data, ap_tracking)
+            }
+
+            Hint::PackBytes(var) => self.pack_bytes(vm, var),
+
+            Hint::Concat(a, b) => self.concat(vm, a, b),
+
+            Hint::Reverse(var) => self.reverse(vm, var),
+
+            Hint::EncodedLen(ptr) => self.encoded_len(vm, ptr, ids_data, ap_tracking),
+
+            Hint::MapIncrement(var, delta) => {
+                let delta = get_integer_from_var_name(delta, vm, ids_data, ap_tracking)?;
+                self.map_list_input(vm, hint, var, |elem| match elem {
+                    Value::ValueFelt(x) => Ok(Value::ValueFelt(x + delta)),
+                    _ => Err("is not a field element"),
+                })
+            }
+
+            Hint::Select(cond, a, b) => {
+                let cond = get_integer_from_var_name(cond, vm, ids_data, ap_tracking)?;
+                let var = if cond != Felt252::ZERO { a } else { b };
+                self.read_program_input(vm, var, constants)
+            }
+
+            Hint::AssertInputEq(a, b) => {
+                if self.input_or_constant(a, constants)? != self.input_or_constant(b, constants)? {
+                    return Err(HintError::CustomHint(
+                        format!("AssertInputEq({a}, {b}): the inputs {a} and {b} differ")
+                            .into_boxed_str(),
+                    ));
+                }
+                Ok(())
+            }
+
+            Hint::AssertLen(var, expected) => self.assert_len(var, *expected),
+
+            Hint::StepCount => {
+                vm.insert_value(vm.get_ap(), Felt252::from(self.steps))?;
+                Ok(())
+            }
         }
     }
 
@@ -85,6 +494,14 @@ impl JuvixHintProcessor {
         exec_scopes: &mut ExecutionScopes,
         size: usize,
     ) -> Result<Relocatable, HintError> {
+        if size == 0 {
+            if self.strict_alloc {
+                return Err(HintError::CustomHint(
+                    format!("Alloc(0) at pc {}: empty allocation", vm.get_pc()).into_boxed_str(),
+                ));
+            }
+            warning::warn(format!("Alloc(0) at pc {}: empty allocation", vm.get_pc()));
+        }
         let memory_exec_scope =
             match exec_scopes.get_mut_ref::<MemoryExecScope>("memory_exec_scope") {
                 Ok(memory_exec_scope) => memory_exec_scope,
@@ -93,6 +510,7 @@ impl JuvixHintProcessor {
                         "memory_exec_scope",
                         Box::new(MemoryExecScope {
                             next_address: vm.add_memory_segment(),
+                            allocated: HashMap::new(),
                         }),
                     );
                     exec_scopes.get_mut_ref::<MemoryExecScope>("memory_exec_scope")?
@@ -100,17 +518,173 @@ impl JuvixHintProcessor {
             };
 
         let addr = memory_exec_scope.next_address;
+        let next_offset = addr.offset.checked_add(size).ok_or_else(|| {
+            HintError::CustomHint(
+                format!(
+                    "Alloc({size}) at pc {}: segment offset overflow",
+                    vm.get_pc()
+                )
+                .into_boxed_str(),
+            )
+        })?;
         vm.insert_value(vm.get_ap(), addr)?;
-        memory_exec_scope.next_address.offset += size;
+        memory_exec_scope.next_address.offset = next_offset;
+        memory_exec_scope.allocated.insert(addr, size);
 
         Ok(addr)
     }
 
-    fn read_program_input(&self, vm: &mut VirtualMachine, var: &String) -> Result<(), HintError> {
-        let val = self.program_input.get(var.as_str());
+    // Writes the encoding of the input variable `var` into the block allocated
+    // by a previous `Alloc` whose address is stored at `ap`.
+    fn read_program_input_into(
+        &self,
+        vm: &mut VirtualMachine,
+        exec_scopes: &mut ExecutionScopes,
+        var: &str,
+    ) -> Result<(), HintError> {
+        let val = self.program_input.try_get(var).ok_or_else(|| {
+            HintError::CustomHint(
+                format!("InputInto({var}): there is no input {var}").into_boxed_str(),
+            )
+        })?;
+        let addr = vm.get_relocatable(vm.get_ap())?;
+        let allocated = exec_scopes
+            .get_ref::<MemoryExecScope>("memory_exec_scope")
+            .ok()
+            .and_then(|scope| scope.allocated.get(&addr).copied())
+            .ok_or_else(|| {
+                HintError::CustomHint(
+                    format!("InputInto({var}): {addr} is not an allocated block").into_boxed_str(),
+                )
+            })?;
+        let size = encoded_size(val);
+        if size > allocated {
+            return Err(HintError::CustomHint(
+                format!(
+                    "InputInto({var}): the value needs {size} memory cells, but only {allocated} were allocated"
+                )
+                .into_boxed_str(),
+            ));
+        }
+        self.read_value_input(vm, addr, val).map(|_| ())
+    }
+
+    // Writes the number of elements of the list input variable `var` to `ap`.
+    fn read_input_len(&self, vm: &mut VirtualMachine, var: &str) -> Result<(), HintError> {
+        match self.program_input.try_get(var) {
+            Some(Value::ValueList(elems)) => {
+                vm.insert_value(vm.get_ap(), elems.len())?;
+                Ok(())
+            }
+            Some(_) => Err(HintError::CustomHint(
+                format!("InputLen({var}): the input is not a list").into_boxed_str(),
+            )),
+            None => Err(HintError::CustomHint(
+                format!("InputLen({var}): there is no input {var}").into_boxed_str(),
+            )),
+        }
+    }
+
+    // Checks that the list input `var` has exactly `expected` elements, so
+    // that a malformed input is reported where it is used.
+    fn assert_len(&self, var: &str, expected: usize) -> Result<(), HintError> {
+        let error = |message: String| {
+            HintError::CustomHint(
+                format!("AssertLen({var}, {expected}): {message}").into_boxed_str(),
+            )
+        };
+        match self.program_input.try_get(var) {
+            Some(Value::ValueList(elems)) if elems.len() == expected => Ok(()),
+            Some(Value::ValueList(elems)) => Err(error(format!(
+                "the input has {} elements, expected {expected}",
+                elems.len()
+            ))),
+            Some(_) => Err(error("the input is not a list".to_string())),
+            None => Err(error(format!("there is no input {var}"))),
+        }
+    }
+
+    fn read_program_input(
+        &self,
+        vm: &mut VirtualMachine,
+        var: &str,
+        constants: &HashMap<String, Felt252>,
+    ) -> Result<(), HintError> {
+        let val = self.input_or_constant(var, constants)?;
+        self.write_value_at_ap(vm, &val)
+    }
+
+    // Returns the input variable `var`, or else the program constant `var`.
+    // Constants are named by the last component of their full path, so
+    // `N` refers to `__main__.N`; a name shared by constants of several
+    // scopes is rejected as ambiguous.
+    fn input_or_constant(
+        &self,
+        var: &str,
+        constants: &HashMap<String, Felt252>,
+    ) -> Result<Cow<'_, Value>, HintError> {
+        if self.program_input.contains_key(var) {
+            return Ok(Cow::Borrowed(self.program_input.get(var)));
+        }
+        let mut matches = constants
+            .iter()
+            .filter(|(name, _)| name.rsplit('.').next() == Some(var));
+        match (matches.next(), matches.next()) {
+            (Some((_, value)), None) => Ok(Cow::Owned(Value::ValueFelt(*value))),
+            (Some(_), Some(_)) => Err(HintError::CustomHint(
+                format!("the constant name {var} is ambiguous").into_boxed_str(),
+            )),
+            (None, _) => Err(HintError::CustomHint(
+                format!("no input or constant named {var}").into_boxed_str(),
+            )),
+        }
+    }
+
+    // Writes the element of the list input variable `var` at the position
+    // given by the reference `index`, like the Input hint writes a variable.
+    fn read_input_index(
+        &self,
+        vm: &mut VirtualMachine,
+        var: &str,
+        index: &str,
+        ids_data: &HashMap<String, HintReference>,
+        ap_tracking: &ApTracking,
+    ) -> Result<(), HintError> {
+        let index = get_integer_from_var_name(index, vm, ids_data, ap_tracking)?;
+        let elems = match self.program_input.try_get(var) {
+            Some(Value::ValueList(elems)) => elems,
+            Some(_) => {
+                return Err(HintError::CustomHint(
+                    format!("InputIndex({var}): the input is not a list").into_boxed_str(),
+                ))
+            }
+            None => {
+                return Err(HintError::CustomHint(
+                    format!("InputIndex({var}): there is no input {var}").into_boxed_str(),
+                ))
+            }
+        };
+        let elem = felt_to_usize(&index)
+            .ok()
+            .and_then(|i| elems.get(i))
+            .ok_or_else(|| {
+                HintError::CustomHint(
+                    format!(
+                        "InputIndex({var}): index {index} out of bounds for a list of length {}",
+                        elems.len()
+                    )
+                    .into_boxed_str(),
+                )
+            })?;
+        self.write_value_at_ap(vm, elem)
+    }
+
+    // Writes felts and booleans at `ap`, and the other values into a fresh
+    // segment whose address is stored at `ap`.
+    fn write_value_at_ap(&self, vm: &mut VirtualMachine, val: &Value) -> Result<(), HintError> {
         let addr = match val {
             Value::ValueFelt(_) | Value::ValueBool(_) => vm.get_ap(),
-            Value::ValueRecord(_) | Value::ValueList(_) => {
+            Value::ValueRecord(_) | Value::ValueList(_) | Value::ValueString(_) => {
                 let segment = vm.add_memory_segment();
                 vm.insert_value(vm.get_ap(), segment)?;
                 segment
@@ -131,6 +705,7 @@ impl JuvixHintProcessor {
             Value::ValueBool(v) => self.read_bool_input(vm, addr, *v),
             Value::ValueRecord(v) => self.read_record_input(vm, addr, v),
             Value::ValueList(v) => self.read_list_input(vm, addr, v),
+            Value::ValueString(v) => self.read_string_input(vm, addr, v),
         }
     }
 
@@ -196,6 +771,115 @@ impl JuvixHintProcessor {
         Ok((addr1 - addr)? + 1)
     }
 
+    // A string is encoded as its length in bytes followed by the UTF-8 bytes
+    // packed into felts, 31 bytes per felt in big-endian order. The last felt
+    // holds the remaining (possibly fewer than 31) bytes.
+    fn read_string_input(
+        &self,
+        vm: &mut VirtualMachine,
+        addr: Relocatable,
+        v: &str,
+    ) -> Result<usize, HintError> {
+        write_packed_bytes(vm, addr, v.as_bytes())
+    }
+
+    // Packs the input variable `var`, a list of bytes, into a fresh segment
+    // the same way as a string and stores the pointer at `ap`.
+    fn pack_bytes(&self, vm: &mut VirtualMachine, var: &str) -> Result<(), HintError> {
+        let invalid = || {
+            HintError::CustomHint(
+                format!("PackBytes({var}): the input is not a list of bytes").into_boxed_str(),
+            )
+        };
+        let bytes = match self.program_input.try_get(var) {
+            Some(Value::ValueList(elems)) => elems
+                .iter()
+                .map(|elem| match elem {
+                    Value::ValueFelt(felt) => felt_to_usize(felt)
+                        .ok()
+                        .and_then(|byte| u8::try_from(byte).ok())
+                        .ok_or_else(invalid),
+                    _ => Err(invalid()),
+                })
+                .collect::<Result<Vec<u8>, HintError>>()?,
+            Some(_) => return Err(invalid()),
+            None => {
+                return Err(HintError::CustomHint(
+                    format!("PackBytes({var}): there is no input {var}").into_boxed_str(),
+                ))
+            }
+        };
+        let segment = vm.add_memory_segment();
+        vm.insert_value(vm.get_ap(), segment)?;
+        write_packed_bytes(vm, segment, &bytes).map(|_| ())
+    }
+
+    // Encodes the concatenation of the list input variables `a` and `b` as a
+    // single list in a fresh segment and stores the pointer at `ap`.
+    fn concat(&self, vm: &mut VirtualMachine, a: &str, b: &str) -> Result<(), HintError> {
+        let get_list = |var: &str| match self.program_input.try_get(var) {
+            Some(Value::ValueList(elems)) => Ok(elems),
+            Some(_) => Err(HintError::CustomHint(
+                format!("Concat({a}, {b}): the input {var} is not a list").into_boxed_str(),
+            )),
+            None => Err(HintError::CustomHint(
+                format!("Concat({a}, {b}): there is no input {var}").into_boxed_str(),
+            )),
+        };
+        let elems: Vec<Value> = get_list(a)?.iter().chain(get_list(b)?).cloned().collect();
+        let segment = vm.add_memory_segment();
+        vm.insert_value(vm.get_ap(), segment)?;
+        self.read_list_input(vm, segment, &elems).map(|_| ())
+    }
+
+    // Writes the list input `var` in reverse order into a fresh segment whose
+    // address is stored at `ap`.
+    fn reverse(&self, vm: &mut VirtualMachine, var: &str) -> Result<(), HintError> {
+        let elems: Vec<Value> = match self.program_input.try_get(var) {
+            Some(Value::ValueList(elems)) => elems.iter().rev().cloned().collect(),
+            Some(_) => {
+                return Err(HintError::CustomHint(
+                    format!("Reverse({var}): the input is not a list").into_boxed_str(),
+                ))
+            }
+            None => {
+                return Err(HintError::CustomHint(
+                    format!("Reverse({var}): there is no input {var}").into_boxed_str(),
+                ))
+            }
+        };
+        let segment = vm.add_memory_segment();
+        vm.insert_value(vm.get_ap(), segment)?;
+        self.read_list_input(vm, segment, &elems).map(|_| ())
+    }
+
+    // Writes the list input `var` with `f` applied to each element into a
+    // fresh segment whose address is stored at `ap`. The element-wise hints
+    // differ only in `f`, which says why it rejects an element.
+    fn map_list_input(
+        &self,
+        vm: &mut VirtualMachine,
+        hint: &Hint,
+        var: &str,
+        f: impl Fn(&Value) -> Result<Value, &'static str>,
+    ) -> Result<(), HintError> {
+        let error = |message: String| {
+            HintError::CustomHint(format!("{}({var}): {message}", hint.name()).into_boxed_str())
+        };
+        let elems: Vec<Value> = match self.program_input.try_get(var) {
+            Some(Value::ValueList(elems)) => elems
+                .iter()
+                .enumerate()
+                .map(|(i, elem)| f(elem).map_err(|reason| error(format!("element {i} {reason}"))))
+                .collect::<Result<_, _>>()?,
+            Some(_) => return Err(error("the input is not a list".to_string())),
+            None => return Err(error(format!("there is no input {var}"))),
+        };
+        let segment = vm.add_memory_segment();
+        vm.insert_value(vm.get_ap(), segment)?;
+        self.read_list_input(vm, segment, &elems).map(|_| ())
+    }
+
     fn read_pointer_value_input(
         &self,
         vm: &mut VirtualMachine,
@@ -212,6 +896,10 @@ impl JuvixHintProcessor {
                 vm.insert_value(addr1, addr2).map_err(HintError::Memory)?;
                 addr2 += self.read_list_input(vm, addr2, v)?
             }
+            Value::ValueString(v) => {
+                vm.insert_value(addr1, addr2).map_err(HintError::Memory)?;
+                addr2 += self.read_string_input(vm, addr2, v)?
+            }
             _ => self.read_value_input(vm, addr1, val).map(|_| ())?,
         }
         Ok(addr2)
@@ -224,14 +912,18 @@ impl JuvixHintProcessor {
     ) -> Result<(), HintError> {
         let beta = Fq::from(get_beta().to_biguint());
 
-        let mut rng = ark_std::test_rng();
-        let (random_x, random_y_squared) = loop {
-            let random_x = Fq::rand(&mut rng);
+        // draws x coordinates until one is on the curve
+        let draw = |rng: &mut dyn RngCore| loop {
+            let random_x = Fq::rand(rng);
             let random_y_squared = random_x * random_x * random_x + random_x + beta;
             if random_y_squared.legendre().is_qr() {
                 break (random_x, random_y_squared);
             }
         };
+        let (random_x, random_y_squared) = match self.seed {
+            Some(seed) => draw(&mut StdRng::seed_from_u64(seed)),
+            None => draw(&mut ark_std::test_rng()),
+        };
 
         let x_bigint: BigUint = random_x.into_bigint().into();
         let y_bigint: BigUint = random_y_squared
@@ -248,25 +940,243 @@ impl JuvixHintProcessor {
 
         Ok(())
     }
+
+    /// Reads the `len` field elements starting at the pointer `input_ptr`.
+    fn read_span(
+        &self,
+        vm: &VirtualMachine,
+        input_ptr: &str,
+        len: &str,
+        ids_data: &HashMap<String, HintReference>,
+        ap_tracking: &ApTracking,
+    ) -> Result<Vec<Felt252>, HintError> {
+        let input_addr = get_ptr_from_var_name(input_ptr, vm, ids_data, ap_tracking)?;
+        let len = felt_to_usize(&get_integer_from_var_name(len, vm, ids_data, ap_tracking)?)?;
+        // make sure the whole input span is addressable
+        (input_addr + len).map_err(HintError::Math)?;
+        Ok(vm
+            .get_integer_range(input_addr, len)?
+            .into_iter()
+            .map(|elem| elem.into_owned())
+            .collect())
+    }
+
+    /// Hashes `len` field elements starting at `input_ptr` with Keccak-256.
+    /// Each element is absorbed as 32 big-endian bytes. The digest is written
+    /// at `out` as two 128-bit limbs: the low limb first, then the high limb.
+    fn keccak(
+        &self,
+        vm: &mut VirtualMachine,
+        input_ptr: &str,
+        len: &str,
+        out: &str,
+        ids_data: &HashMap<String, HintReference>,
+        ap_tracking: &ApTracking,
+    ) -> Result<(), HintError> {
+        let input = self.read_span(vm, input_ptr, len, ids_data, ap_tracking)?;
+        let out_addr = get_ptr_from_var_name(out, vm, ids_data, ap_tracking)?;
+
+        let mut hasher = Keccak256::new();
+        for elem in input {
+            hasher.update(elem.to_bytes_be());
+        }
+        let digest = hasher.finalize();
+
+        let mut high = [0u8; 16];
+        let mut low = [0u8; 16];
+        high.copy_from_slice(&digest[..16]);
+        low.copy_from_slice(&digest[16..]);
+        vm.insert_value(out_addr, Felt252::from(u128::from_be_bytes(low)))?;
+        vm.insert_value((out_addr + 1)?, Felt252::from(u128::from_be_bytes(high)))?;
+
+        Ok(())
+    }
+
+    /// Hashes `len` field elements starting at `input_ptr` with Poseidon and
+    /// writes the digest at `out`. This is the `poseidon_hash_many` sponge of
+    /// the Cairo library: Hades permutation over a state of width 3 with rate
+    /// 2, where the input is padded with a single 1 followed by zeros to an
+    /// even length and the digest is the first element of the final state.
+    fn poseidon(
+        &self,
+        vm: &mut VirtualMachine,
+        input_ptr: &str,
+        len: &str,
+        out: &str,
+        ids_data: &HashMap<String, HintReference>,
+        ap_tracking: &ApTracking,
+    ) -> Result<(), HintError> {
+        let input = self
+            .read_span(vm, input_ptr, len, ids_data, ap_tracking)?
+            .iter()
+            .map(felt_to_field_element)
+            .collect::<Result<Vec<_>, _>>()?;
+        let out_addr = get_ptr_from_var_name(out, vm, ids_data, ap_tracking)?;
+        let digest = poseidon_hash_many(&input);
+        vm.insert_value(out_addr, Felt252::from_bytes_be(&digest.to_bytes_be()))?;
+        Ok(())
+    }
+
+    /// Writes 1 at `ap` if `a < b` when both are interpreted as signed
+    /// integers, and 0 otherwise.
+    fn signed_lt(
+        &self,
+        vm: &mut VirtualMachine,
+        a: &str,
+        b: &str,
+        ids_data: &HashMap<String, HintReference>,
+        ap_tracking: &ApTracking,
+    ) -> Result<(), HintError> {
+        let a = get_integer_from_var_name(a, vm, ids_data, ap_tracking)?;
+        let b = get_integer_from_var_name(b, vm, ids_data, ap_tracking)?;
+        let lt = felt_to_signed(&a) < felt_to_signed(&b);
+        vm.insert_value(vm.get_ap(), Felt252::from(lt as u8))?;
+        Ok(())
+    }
+
+    /// Writes 1 at `ap` if `0 <= var < bound`, comparing the canonical
+    /// representations of the field elements as unsigned integers, and 0
+    /// otherwise.
+    fn range_check(
+        &self,
+        vm: &mut VirtualMachine,
+        var: &str,
+        bound: &str,
+        ids_data: &HashMap<String, HintReference>,
+        ap_tracking: &ApTracking,
+    ) -> Result<(), HintError> {
+        let value = get_integer_from_var_name(var, vm, ids_data, ap_tracking)?;
+        let bound = get_integer_from_var_name(bound, vm, ids_data, ap_tracking)?;
+        let in_range = value.to_biguint() < bound.to_biguint();
+        vm.insert_value(vm.get_ap(), Felt252::from(in_range as u8))?;
+        Ok(())
+    }
+
+    /// Writes at `ap` the number of set bits in the canonical representation
+    /// of the field element `var`.
+    fn pop_count(
+        &self,
+        vm: &mut VirtualMachine,
+        var: &str,
+        ids_data: &HashMap<String, HintReference>,
+        ap_tracking: &ApTracking,
+    ) -> Result<(), HintError> {
+        let value = get_integer_from_var_name(var, vm, ids_data, ap_tracking)?;
+        let count: u32 = value
+            .to_bytes_le()
+            .iter()
+            .map(|byte| byte.count_ones())
+            .sum();
+        vm.insert_value(vm.get_ap(), Felt252::from(count))?;
+        Ok(())
+    }
+
+    /// Writes at `ap` the number of cells of the encoded value at `ptr`, as
+    /// walked by `encoded_value_end`.
+    fn encoded_len(
+        &self,
+        vm: &mut VirtualMachine,
+        ptr: &str,
+        ids_data: &HashMap<String, HintReference>,
+        ap_tracking: &ApTracking,
+    ) -> Result<(), HintError> {
+        let start = get_ptr_from_var_name(ptr, vm, ids_data, ap_tracking)?;
+        let end = encoded_value_end(vm, start).map_err(|message| {
+            HintError::CustomHint(format!("EncodedLen({ptr}): {message}").into_boxed_str())
+        })?;
+        vm.insert_value(vm.get_ap(), Felt252::from((end - start)?))?;
+        Ok(())
+    }
+
+    /// Writes the Pedersen hash of the field elements `a` and `b` at `ap`.
+    fn pedersen(
+        &self,
+        vm: &mut VirtualMachine,
+        a: &str,
+        b: &str,
+        ids_data: &HashMap<String, HintReference>,
+        ap_tracking: &ApTracking,
+    ) -> Result<(), HintError> {
+        let a = get_integer_from_var_name(a, vm, ids_data, ap_tracking)?;
+        let b = get_integer_from_var_name(b, vm, ids_data, ap_tracking)?;
+        let digest = pedersen_hash(&felt_to_field_element(&a)?, &felt_to_field_element(&b)?);
+        vm.insert_value(vm.get_ap(), Felt252::from_bytes_be(&digest.to_bytes_be()))?;
+        Ok(())
+    }
+
+    /// Writes at `ap` the root of the Merkle tree over the `len` leaves
+    /// starting at `leaves_ptr`. Each level is built by hashing adjacent
+    /// pairs of nodes, left to right; a level with an odd number of nodes
+    /// has its last node paired with itself. The root of a single leaf is
+    /// the leaf itself. Fails if there are no leaves.
+    fn merkle_root(
+        &self,
+        vm: &mut VirtualMachine,
+        hash: MerkleHash,
+        leaves_ptr: &str,
+        len: &str,
+        ids_data: &HashMap<String, HintReference>,
+        ap_tracking: &ApTracking,
+    ) -> Result<(), HintError> {
+        let mut level = self
+            .read_span(vm, leaves_ptr, len, ids_data, ap_tracking)?
+            .iter()
+            .map(felt_to_field_element)
+            .collect::<Result<Vec<_>, _>>()?;
+        if level.is_empty() {
+            return Err(HintError::CustomHint(
+                "MerkleRoot: the tree has no leaves".into(),
+            ));
+        }
+        let combine: fn(FieldElement, FieldElement) -> FieldElement = match hash {
+            MerkleHash::Poseidon => poseidon_hash,
+            MerkleHash::Pedersen => |x, y| pedersen_hash(&x, &y),
+        };
+        while level.len() > 1 {
+            level = level
+                .chunks(2)
+                .map(|pair| combine(pair[0], *pair.last().unwrap()))
+                .collect();
+        }
+        vm.insert_value(vm.get_ap(), Felt252::from_bytes_be(&level[0].to_bytes_be()))?;
+        Ok(())
+    }
 }
 
-impl HintProcessorLogic for JuvixHintProcessor {
+impl HintProcessorLogic for JuvixHintProcessor<'_> {
     fn compile_hint(
         &self,
         //Block of hint code as String
         hint_code: &str,
         //Ap Tracking Data corresponding to the Hint
-        _ap_tracking_data: &cairo_vm::serde::deserialize_program::ApTracking,
+        ap_tracking_data: &ApTracking,
         //Map from variable name to reference id number
         //(may contain other variables aside from those used by the hint)
-        _reference_ids: &HashMap<String, usize>,
+        reference_ids: &HashMap<String, usize>,
         //List of all references (key corresponds to element of the previous dictionary)
-        _references: &[HintReference],
+        references: &[HintReference],
     ) -> Result<Box<dyn Any>, VirtualMachineError> {
-        let data = hint_code
-            .parse::<Hint>()
-            .map_err(|e| VirtualMachineError::CompileHintFail(e.message.into_boxed_str()))?;
-        Ok(any_box!(data))
+        let hint = match (hint_code.parse::<Hint>(), &self.builtin_hint_processor) {
+            (Ok(hint), _) => hint,
+            (Err(_), Some(builtin_hint_processor)) => {
+                return builtin_hint_processor.compile_hint(
+                    hint_code,
+                    ap_tracking_data,
+                    reference_ids,
+                    references,
+                )
+            }
+            (Err(e), None) => {
+                return Err(VirtualMachineError::CompileHintFail(
+                    e.message.into_boxed_str(),
+                ))
+            }
+        };
+        Ok(any_box!(JuvixHintData {
+            hint,
+            ids_data: get_ids_data(reference_ids, references)?,
+            ap_tracking: ap_tracking_data.clone(),
+        }))
     }
 
     fn execute_hint(
@@ -280,20 +1190,40 @@ impl HintProcessorLogic for JuvixHintProcessor {
         //Data structure that can be downcasted to the structure generated by compile_hint
         hint_data: &Box<dyn Any>,
         //Constant values extracted from the program specification.
-        _constants: &HashMap<String, Felt252>,
+        constants: &HashMap<String, Felt252>,
     ) -> Result<(), HintError> {
-        let hint: &Hint = hint_data.downcast_ref().ok_or(HintError::WrongHintData)?;
-        self.execute(vm, exec_scopes, hint)
+        let data: &JuvixHintData = match (
+            hint_data.downcast_ref(),
+            self.builtin_hint_processor.as_mut(),
+        ) {
+            (Some(data), _) => data,
+            (None, Some(builtin_hint_processor)) => {
+                return builtin_hint_processor.execute_hint(vm, exec_scopes, hint_data, constants)
+            }
+            (None, None) => return Err(HintError::WrongHintData),
+        };
+        self.execute(
+            vm,
+            exec_scopes,
+            &data.hint,
+            &data.ids_data,
+            &data.ap_tracking,
+            constants,
+        )
     }
 }
 
-impl ResourceTracker for JuvixHintProcessor {
+impl ResourceTracker for JuvixHintProcessor<'_> {
     fn consumed(&self) -> bool {
-        self.run_resources.consumed()
+        self.interrupted() || self.run_resources.consumed()
     }
 
     fn consume_step(&mut self) {
-        self.run_resources.consume_step()
+        self.steps += 1;
+        self.run_resources.consume_step();
+        if let Some(ref mut progress) = self.progress {
+            progress.update(self.steps, self.run_resources.get_n_steps());
+        }
     }
 
     fn get_n_steps(&self) -> Option<usize> {
@@ -304,3 +1234,1123 @@ impl ResourceTracker for JuvixHintProcessor {
         &self.run_resources
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    /// Creates a VM whose current frame holds `ids` at `fp`, `fp + 1`, ...,
+    /// and returns it together with the references to these variables.
+    fn setup_vm(
+        ids: &[(&str, MaybeRelocatable)],
+    ) -> (VirtualMachine, HashMap<String, HintReference>) {
+        let mut vm = VirtualMachine::new(false);
+        // program and execution segments
+        vm.add_memory_segment();
+        let fp = vm.add_memory_segment();
+        let mut ids_data = HashMap::new();
+        for (i, (name, value)) in ids.iter().enumerate() {
+            vm.insert_value((fp + i).unwrap(), value.clone()).unwrap();
+            ids_data.insert(name.to_string(), HintReference::new_simple(i as i32));
+        }
+        vm.set_ap(ids.len());
+        (vm, ids_data)
+    }
+
+    fn execute_hint(
+        vm: &mut VirtualMachine,
+        ids_data: &HashMap<String, HintReference>,
+        hint: &Hint,
+    ) -> Result<(), HintError> {
+        JuvixHintProcessor::new(ProgramInput::new(HashMap::new())).execute(
+            vm,
+            &mut ExecutionScopes::new(),
+            hint,
+            ids_data,
+            &ApTracking::new(),
+            &HashMap::new(),
+        )
+    }
+
+    #[rstest]
+    #[case(
+        &[0],
+        "290decd9548b62a8d60345a988386fc84ba6bc95484008f6362f93160ef3e563"
+    )]
+    #[case(
+        &[1],
+        "b10e2d527612073b26eecdfd717e6a320cf44b4afac2b0732d9fcbe2b7fa0cf6"
+    )]
+    #[case(
+        &[],
+        "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"
+    )]
+    fn test_keccak(#[case] input: &[u64], #[case] digest: &str) {
+        let (mut vm, ids_data) = setup_vm(&[
+            ("ptr", Relocatable::from((2, 0)).into()),
+            ("len", Felt252::from(input.len()).into()),
+            ("out", Relocatable::from((3, 0)).into()),
+        ]);
+        let input_ptr = vm.add_memory_segment();
+        let out_ptr = vm.add_memory_segment();
+        for (i, x) in input.iter().enumerate() {
+            vm.insert_value((input_ptr + i).unwrap(), Felt252::from(*x))
+                .unwrap();
+        }
+        execute_hint(
+            &mut vm,
+            &ids_data,
+            &Hint::Keccak("ptr".into(), "len".into(), "out".into()),
+        )
+        .unwrap();
+        assert_eq!(
+            *vm.get_integer(out_ptr).unwrap(),
+            Felt252::from_hex(&digest[32..]).unwrap()
+        );
+        assert_eq!(
+            *vm.get_integer((out_ptr + 1usize).unwrap()).unwrap(),
+            Felt252::from_hex(&digest[..32]).unwrap()
+        );
+    }
+
+    #[rstest]
+    #[case(
+        &[
+            "0x9bf52404586087391c5fbb42538692e7ca2149bac13c145ae4230a51a6fc47",
+            "0x40304159ee9d2d611120fbd7c7fb8020cc8f7a599bfa108e0e085222b862c0",
+            "0x46286e4f3c450761d960d6a151a9c0988f9e16f8a48d4c0a85817c009f806a",
+        ],
+        "0x1ec38b38dc88bac7b0ed6ff6326f975a06a59ac601b417745fd412a5d38e4f7"
+    )]
+    fn test_poseidon(#[case] input: &[&str], #[case] digest: &str) {
+        let (mut vm, ids_data) = setup_vm(&[
+            ("ptr", Relocatable::from((2, 0)).into()),
+            ("len", Felt252::from(input.len()).into()),
+            ("out", Relocatable::from((3, 0)).into()),
+        ]);
+        let input_ptr = vm.add_memory_segment();
+        let out_ptr = vm.add_memory_segment();
+        for (i, x) in input.iter().enumerate() {
+            vm.insert_value((input_ptr + i).unwrap(), Felt252::from_hex(x).unwrap())
+                .unwrap();
+        }
+        execute_hint(
+            &mut vm,
+            &ids_data,
+            &Hint::Poseidon("ptr".into(), "len".into(), "out".into()),
+        )
+        .unwrap();
+        assert_eq!(
+            *vm.get_integer(out_ptr).unwrap(),
+            Felt252::from_hex(digest).unwrap()
+        );
+    }
+
+    #[rstest]
+    #[case(
+        "0x3d937c035c878245caf64531a5756109c53068da139362728feb561405371cb",
+        "0x208a0a10250e382e1e4bbe2880906c2791bf6275695e02fbbc6aeff9cd8b31a",
+        "0x30e480bed5fe53fa909cc0f8c4d99b8f9f2c016be4c41e13a4848797979c662"
+    )]
+    #[case(
+        "0x58f580910a6ca59b28927c08fe6c43e2e303ca384badc365795fc645d479d45",
+        "0x78734f65a067be9bdb39de18434d71e79f7b6466a4b66bbd979ab9e7515fe0b",
+        "0x68cc0b76cddd1dd4ed2301ada9b7c872b23875d5ff837b3a87993e0d9996b87"
+    )]
+    fn test_pedersen(#[case] a: &str, #[case] b: &str, #[case] digest: &str) {
+        let (mut vm, ids_data) = setup_vm(&[
+            ("a", Felt252::from_hex(a).unwrap().into()),
+            ("b", Felt252::from_hex(b).unwrap().into()),
+        ]);
+        execute_hint(&mut vm, &ids_data, &Hint::Pedersen("a".into(), "b".into())).unwrap();
+        assert_eq!(
+            *vm.get_integer(vm.get_ap()).unwrap(),
+            Felt252::from_hex(digest).unwrap()
+        );
+    }
+
+    fn merkle_root(hash: MerkleHash, leaves: &[Felt252]) -> Result<Felt252, HintError> {
+        let (mut vm, ids_data) = setup_vm(&[
+            ("leaves", Relocatable::from((2, 0)).into()),
+            ("len", Felt252::from(leaves.len()).into()),
+        ]);
+        let leaves_ptr = vm.add_memory_segment();
+        for (i, leaf) in leaves.iter().enumerate() {
+            vm.insert_value((leaves_ptr + i).unwrap(), *leaf).unwrap();
+        }
+        execute_hint(
+            &mut vm,
+            &ids_data,
+            &Hint::MerkleRoot(hash, "leaves".into(), "len".into()),
+        )?;
+        Ok(*vm.get_integer(vm.get_ap()).unwrap())
+    }
+
+    #[test]
+    fn test_merkle_root() {
+        let a =
+            Felt252::from_hex("0x3d937c035c878245caf64531a5756109c53068da139362728feb561405371cb")
+                .unwrap();
+        let b =
+            Felt252::from_hex("0x208a0a10250e382e1e4bbe2880906c2791bf6275695e02fbbc6aeff9cd8b31a")
+                .unwrap();
+        let ab =
+            Felt252::from_hex("0x30e480bed5fe53fa909cc0f8c4d99b8f9f2c016be4c41e13a4848797979c662")
+                .unwrap();
+        let to_felt = |digest: FieldElement| Felt252::from_bytes_be(&digest.to_bytes_be());
+        let pedersen = |x: Felt252, y: Felt252| {
+            to_felt(pedersen_hash(
+                &felt_to_field_element(&x).unwrap(),
+                &felt_to_field_element(&y).unwrap(),
+            ))
+        };
+        let poseidon = |x: Felt252, y: Felt252| {
+            to_felt(poseidon_hash(
+                felt_to_field_element(&x).unwrap(),
+                felt_to_field_element(&y).unwrap(),
+            ))
+        };
+
+        assert_eq!(merkle_root(MerkleHash::Pedersen, &[a]).unwrap(), a);
+        assert_eq!(merkle_root(MerkleHash::Pedersen, &[a, b]).unwrap(), ab);
+        // the odd leaf is paired with itself
+        assert_eq!(
+            merkle_root(MerkleHash::Pedersen, &[a, b, a]).unwrap(),
+            pedersen(ab, pedersen(a, a))
+        );
+        assert_eq!(
+            merkle_root(MerkleHash::Poseidon, &[a, b, b, a]).unwrap(),
+            poseidon(poseidon(a, b), poseidon(b, a))
+        );
+        assert!(merkle_root(MerkleHash::Poseidon, &[]).is_err());
+    }
+
+    #[rstest]
+    #[case("", &[])]
+    #[case("hello", &["0x68656c6c6f"])]
+    #[case(
+        "0123456789012345678901234567890123456789",
+        &[
+            "0x30313233343536373839303132333435363738393031323334353637383930",
+            "0x313233343536373839",
+        ]
+    )]
+    fn test_string_input(#[case] input: &str, #[case] chunks: &[&str]) {
+        let hint_processor = JuvixHintProcessor::new(ProgramInput::new(HashMap::from([(
+            String::from("X"),
+            Value::ValueString(String::from(input)),
+        )])));
+        let (mut vm, _) = setup_vm(&[]);
+        hint_processor
+            .read_program_input(&mut vm, "X", &HashMap::new())
+            .unwrap();
+        let addr = vm.get_relocatable(vm.get_ap()).unwrap();
+        assert_eq!(*vm.get_integer(addr).unwrap(), Felt252::from(input.len()));
+        for (i, chunk) in chunks.iter().enumerate() {
+            assert_eq!(
+                *vm.get_integer((addr + (i + 1)).unwrap()).unwrap(),
+                Felt252::from_hex(chunk).unwrap()
+            );
+        }
+        assert!(vm
+            .get_maybe(&(addr + (chunks.len() + 1)).unwrap())
+            .is_none());
+    }
+
+    #[rstest]
+    #[case(r#"{"X": 7}"#, 1)]
+    #[case(r#"{"X": [1, {"A": 2, "B": [true]}]}"#, 13)]
+    #[case(r#"{"X": {"str": "hello"}}"#, 2)]
+    fn test_input_into(#[case] input: &str, #[case] size: usize) {
+        let program_input = ProgramInput::from_json(input).unwrap();
+        let val = program_input.get("X").clone();
+        let hint_processor = JuvixHintProcessor::new(program_input);
+        let (mut vm, ids_data) = setup_vm(&[]);
+        let mut exec_scopes = ExecutionScopes::new();
+        let mut execute = |vm: &mut VirtualMachine, hint: Hint| {
+            hint_processor.execute(
+                vm,
+                &mut exec_scopes,
+                &hint,
+                &ids_data,
+                &ApTracking::new(),
+                &HashMap::new(),
+            )
+        };
+        assert_eq!(encoded_size(&val), size);
+
+        // too small
+        execute(&mut vm, Hint::Alloc(size - 1)).unwrap();
+        assert!(execute(&mut vm, Hint::InputInto("X".into())).is_err());
+
+        vm.set_ap(vm.get_ap().offset + 1);
+        execute(&mut vm, Hint::Alloc(size)).unwrap();
+        execute(&mut vm, Hint::InputInto("X".into())).unwrap();
+        let addr = vm.get_relocatable(vm.get_ap()).unwrap();
+        assert_eq!(addr, Relocatable::from((2, size - 1)));
+
+        // the same encoding as the one produced by the Input hint
+        let mut expected_vm = VirtualMachine::new(false);
+        expected_vm.add_memory_segment();
+        expected_vm.add_memory_segment();
+        let expected_addr = expected_vm.add_memory_segment();
+        assert_eq!(
+            hint_processor
+                .read_value_input(&mut expected_vm, expected_addr, &val)
+                .unwrap(),
+            size
+        );
+        for i in 0..size {
+            assert_eq!(
+                vm.get_maybe(&(addr + i).unwrap()).map(|x| x.get_int()),
+                expected_vm
+                    .get_maybe(&(expected_addr + i).unwrap())
+                    .map(|x| x.get_int())
+            );
+        }
+    }
+
+    /// Decodes the value stored (inline or behind a pointer) at `cell`. The
+    /// encoding does not record field names or element types, so these are
+    /// taken from `shape`.
+    fn decode_value(vm: &VirtualMachine, cell: Relocatable, shape: &Value) -> Value {
+        match shape {
+            Value::ValueFelt(_) => Value::ValueFelt(*vm.get_integer(cell).unwrap()),
+            Value::ValueBool(_) => {
+                Value::ValueBool(*vm.get_integer(cell).unwrap() == Felt252::ZERO)
+            }
+            Value::ValueRecord(fields) => {
+                let addr = vm.get_relocatable(cell).unwrap();
+                Value::ValueRecord(
+                    fields
+                        .iter()
+                        .enumerate()
+                        .map(|(i, (name, field))| {
+                            (name.clone(), decode_value(vm, (addr + i).unwrap(), field))
+                        })
+                        .collect(),
+                )
+            }
+            Value::ValueList(elems) => {
+                let mut addr = vm.get_relocatable(cell).unwrap();
+                let mut decoded = Vec::new();
+                for elem in elems {
+                    assert_eq!(*vm.get_integer(addr).unwrap(), Felt252::from(get_cid(1)));
+                    decoded.push(decode_value(vm, (addr + 1_usize).unwrap(), elem));
+                    addr = vm.get_relocatable((addr + 2_usize).unwrap()).unwrap();
+                }
+                assert_eq!(*vm.get_integer(addr).unwrap(), Felt252::from(get_cid(0)));
+                Value::ValueList(decoded)
+            }
+            Value::ValueString(_) => {
+                let addr = vm.get_relocatable(cell).unwrap();
+                let len = felt_to_usize(&vm.get_integer(addr).unwrap()).unwrap();
+                let mut bytes = Vec::with_capacity(len);
+                for (i, start) in (0..len).step_by(STRING_CHUNK_SIZE).enumerate() {
+                    let chunk = vm.get_integer((addr + (i + 1)).unwrap()).unwrap();
+                    let chunk_len = STRING_CHUNK_SIZE.min(len - start);
+                    bytes.extend_from_slice(&chunk.to_bytes_be()[32 - chunk_len..]);
+                }
+                Value::ValueString(String::from_utf8(bytes).unwrap())
+            }
+        }
+    }
+
+    #[test]
+    fn test_bool_list_input() {
+        let hint_processor = JuvixHintProcessor::new(
+            ProgramInput::from_json(r#"{"X": [true, false, true]}"#).unwrap(),
+        );
+        let (mut vm, _) = setup_vm(&[]);
+        hint_processor
+            .read_program_input(&mut vm, "X", &HashMap::new())
+            .unwrap();
+        let addr = vm.get_relocatable(vm.get_ap()).unwrap();
+        // bools are single words stored inline in the cons cells
+        let cell = |i: usize| (addr + i).unwrap();
+        let expected: [MaybeRelocatable; 10] = [
+            get_cid(1).into(),
+            0.into(),
+            cell(3).into(),
+            get_cid(1).into(),
+            1.into(),
+            cell(6).into(),
+            get_cid(1).into(),
+            0.into(),
+            cell(9).into(),
+            get_cid(0).into(),
+        ];
+        for (i, value) in expected.iter().enumerate() {
+            assert_eq!(vm.get_maybe(&cell(i)).as_ref(), Some(value));
+        }
+        assert!(vm.get_maybe(&cell(expected.len())).is_none());
+        assert_eq!(
+            decode_value(&vm, vm.get_ap(), hint_processor.program_input.get("X")),
+            *hint_processor.program_input.get("X")
+        );
+    }
+
+    #[test]
+    fn test_record_list_input() {
+        let hint_processor = JuvixHintProcessor::new(
+            ProgramInput::from_json(r#"{"X": [{"a": 1}, {"a": 2}]}"#).unwrap(),
+        );
+        let (mut vm, _) = setup_vm(&[]);
+        hint_processor
+            .read_program_input(&mut vm, "X", &HashMap::new())
+            .unwrap();
+        let addr = vm.get_relocatable(vm.get_ap()).unwrap();
+        // each cons cell points to its record, which directly follows it
+        let cell = |i: usize| (addr + i).unwrap();
+        let expected: [MaybeRelocatable; 9] = [
+            get_cid(1).into(),
+            cell(3).into(),
+            cell(4).into(),
+            1.into(),
+            get_cid(1).into(),
+            cell(7).into(),
+            cell(8).into(),
+            2.into(),
+            get_cid(0).into(),
+        ];
+        for (i, value) in expected.iter().enumerate() {
+            assert_eq!(vm.get_maybe(&cell(i)).as_ref(), Some(value));
+        }
+        assert!(vm.get_maybe(&cell(expected.len())).is_none());
+        assert_eq!(
+            decode_value(&vm, vm.get_ap(), hint_processor.program_input.get("X")),
+            *hint_processor.program_input.get("X")
+        );
+    }
+
+    #[rstest]
+    #[case(r#"{"X": {"a": [1, 2], "b": {"c": true}}}"#)]
+    #[case(r#"{"X": [[1, 2], [], [{"a": [false]}]]}"#)]
+    #[case(r#"{"X": {"str": "abc"}}"#)]
+    #[case(
+        r#"{"X": {"s": {"str": ""}, "l": [{"str": "a string longer than one chunk of packed bytes"}]}}"#
+    )]
+    fn test_nested_input_roundtrip(#[case] input: &str) {
+        let hint_processor = JuvixHintProcessor::new(ProgramInput::from_json(input).unwrap());
+        let (mut vm, _) = setup_vm(&[]);
+        hint_processor
+            .read_program_input(&mut vm, "X", &HashMap::new())
+            .unwrap();
+        let val = hint_processor.program_input.get("X");
+        assert_eq!(decode_value(&vm, vm.get_ap(), val), *val);
+    }
+
+    #[rstest]
+    #[case(r#"{"X": {"a": [1, 2], "b": {"c": true}, "d": {"str": "abc"}}}"#)]
+    #[case(r#"{"X": [[1, 2], [], [{"a": [false]}]]}"#)]
+    #[case(r#"{"X": []}"#)]
+    fn test_encoded_len(#[case] input: &str) {
+        let hint_processor = JuvixHintProcessor::new(ProgramInput::from_json(input).unwrap());
+        let (mut vm, _) = setup_vm(&[]);
+        hint_processor
+            .read_program_input(&mut vm, "X", &HashMap::new())
+            .unwrap();
+        let ids_data = HashMap::from([("ptr".to_string(), HintReference::new_simple(0))]);
+        vm.set_ap(1);
+        execute_hint(&mut vm, &ids_data, &Hint::EncodedLen("ptr".into())).unwrap();
+        assert_eq!(
+            *vm.get_integer(vm.get_ap()).unwrap(),
+            Felt252::from(encoded_size(hint_processor.program_input.get("X")))
+        );
+    }
+
+    #[rstest]
+    #[case(0, r#"[1, {"b": [2, 3]}]"#)]
+    #[case(1, r#"{"str": "abc"}"#)]
+    #[case(2, r#"[]"#)]
+    fn test_encoded_len_nested(#[case] field: usize, #[case] value: &str) {
+        let input = r#"{"X": {"a": [1, {"b": [2, 3]}], "s": {"str": "abc"}, "l": [], "n": 4}}"#;
+        let hint_processor = JuvixHintProcessor::new(ProgramInput::from_json(input).unwrap());
+        let (mut vm, _) = setup_vm(&[]);
+        hint_processor
+            .read_program_input(&mut vm, "X", &HashMap::new())
+            .unwrap();
+        // the pointer to a field of X leads into the middle of the block of X
+        let record = vm.get_relocatable(vm.get_ap()).unwrap();
+        let nested = vm.get_relocatable((record + field).unwrap()).unwrap();
+        vm.insert_value((vm.get_ap() + 1_usize).unwrap(), nested)
+            .unwrap();
+        let ids_data = HashMap::from([("ptr".to_string(), HintReference::new_simple(1))]);
+        vm.set_ap(2);
+        execute_hint(&mut vm, &ids_data, &Hint::EncodedLen("ptr".into())).unwrap();
+        let value = ProgramInput::from_json(&format!(r#"{{"V": {value}}}"#)).unwrap();
+        assert_eq!(
+            *vm.get_integer(vm.get_ap()).unwrap(),
+            Felt252::from(encoded_size(value.get("V")))
+        );
+    }
+
+    #[rstest]
+    #[case(r#"{"X": [[1, 2], [], [{"a": [false]}]]}"#)]
+    #[case(r#"{"X": {"a": {"c": 5}, "b": [1, 2]}}"#)]
+    #[case(r#"{"X": [{"a": 1}, {"a": 2}]}"#)]
+    #[case(r#"{"X": {"str": "a string longer than one packed felt"}}"#)]
+    fn test_encoded_len_followed(#[case] input: &str) {
+        let hint_processor = JuvixHintProcessor::new(ProgramInput::from_json(input).unwrap());
+        let (mut vm, _) = setup_vm(&[]);
+        hint_processor
+            .read_program_input(&mut vm, "X", &HashMap::new())
+            .unwrap();
+        let val = hint_processor.program_input.get("X");
+        // the program keeps writing to the segment after the value
+        let addr = vm.get_relocatable(vm.get_ap()).unwrap();
+        let after = (addr + encoded_size(val)).unwrap();
+        vm.insert_value(after, Felt252::from(7)).unwrap();
+        vm.insert_value((after + 1_usize).unwrap(), addr).unwrap();
+        vm.insert_value((after + 2_usize).unwrap(), (after + 5_usize).unwrap())
+            .unwrap();
+        let ids_data = HashMap::from([("ptr".to_string(), HintReference::new_simple(0))]);
+        vm.set_ap(1);
+        execute_hint(&mut vm, &ids_data, &Hint::EncodedLen("ptr".into())).unwrap();
+        assert_eq!(
+            *vm.get_integer(vm.get_ap()).unwrap(),
+            Felt252::from(encoded_size(val))
+        );
+    }
+
+    #[test]
+    fn test_reset() {
+        let program_content = std::fs::read("tests/ec_random.json").unwrap();
+        let cairo_run_config = cairo_vm::cairo_run::CairoRunConfig {
+            proof_mode: true,
+            layout: "small",
+            ..Default::default()
+        };
+        let mut hint_processor = JuvixHintProcessor::new(ProgramInput::new(HashMap::new()));
+        let run = |hint_processor: &mut JuvixHintProcessor| {
+            let (_, mut vm) =
+                cairo_vm::cairo_run::cairo_run(&program_content, &cairo_run_config, hint_processor)
+                    .unwrap();
+            let mut output = String::new();
+            vm.write_output(&mut output).unwrap();
+            output
+        };
+        let first = run(&mut hint_processor);
+        hint_processor.reset();
+        assert_eq!(run(&mut hint_processor), first);
+    }
+
+    #[rstest]
+    #[case(false)]
+    #[case(true)]
+    fn test_alloc_zero(#[case] strict: bool) {
+        let mut hint_processor = JuvixHintProcessor::new(ProgramInput::new(HashMap::new()));
+        if strict {
+            hint_processor = hint_processor.with_strict_alloc();
+        }
+        let (mut vm, ids_data) = setup_vm(&[]);
+        let result = hint_processor.execute(
+            &mut vm,
+            &mut ExecutionScopes::new(),
+            &Hint::Alloc(0),
+            &ids_data,
+            &ApTracking::new(),
+            &HashMap::new(),
+        );
+        assert_eq!(result.is_err(), strict);
+    }
+
+    #[rstest]
+    #[case(1)]
+    #[case(8)]
+    fn test_alloc_zeroed(#[case] size: usize) {
+        let hint_processor = JuvixHintProcessor::new(ProgramInput::new(HashMap::new()));
+        let (mut vm, ids_data) = setup_vm(&[]);
+        let mut exec_scopes = ExecutionScopes::new();
+        hint_processor
+            .execute(
+                &mut vm,
+                &mut exec_scopes,
+                &Hint::AllocZero(size),
+                &ids_data,
+                &ApTracking::new(),
+                &HashMap::new(),
+            )
+            .unwrap();
+        let addr = vm.get_relocatable(vm.get_ap()).unwrap();
+        for i in 0..size {
+            assert_eq!(*vm.get_integer((addr + i).unwrap()).unwrap(), Felt252::ZERO);
+        }
+        assert!(vm.get_maybe(&(addr + size).unwrap()).is_none());
+    }
+
+    #[test]
+    fn test_alloc_overflow() {
+        let hint_processor = JuvixHintProcessor::new(ProgramInput::new(HashMap::new()));
+        let (mut vm, ids_data) = setup_vm(&[]);
+        let mut exec_scopes = ExecutionScopes::new();
+        let mut execute = |vm: &mut VirtualMachine, hint: Hint| {
+            hint_processor.execute(
+                vm,
+                &mut exec_scopes,
+                &hint,
+                &ids_data,
+                &ApTracking::new(),
+                &HashMap::new(),
+            )
+        };
+        execute(&mut vm, Hint::Alloc(1)).unwrap();
+        vm.set_ap(vm.get_ap().offset + 1);
+        assert!(matches!(
+            execute(&mut vm, Hint::Alloc(usize::MAX)),
+            Err(HintError::CustomHint(msg)) if msg.contains("overflow")
+        ));
+    }
+
+    #[rstest]
+    #[case(r#"{"X": []}"#, Some(0))]
+    #[case(r#"{"X": [1, {"A": 2}, [3, 4]]}"#, Some(3))]
+    #[case(r#"{"X": 7}"#, None)]
+    #[case(r#"{"X": {"str": "abc"}}"#, None)]
+    #[case(r#"{"Y": [1]}"#, None)]
+    fn test_input_len(#[case] input: &str, #[case] expected: Option<usize>) {
+        let hint_processor = JuvixHintProcessor::new(ProgramInput::from_json(input).unwrap());
+        let (mut vm, ids_data) = setup_vm(&[]);
+        let result = hint_processor.execute(
+            &mut vm,
+            &mut ExecutionScopes::new(),
+            &Hint::InputLen("X".into()),
+            &ids_data,
+            &ApTracking::new(),
+            &HashMap::new(),
+        );
+        match expected {
+            Some(len) => {
+                result.unwrap();
+                assert_eq!(*vm.get_integer(vm.get_ap()).unwrap(), Felt252::from(len));
+            }
+            None => assert!(result.is_err()),
+        }
+    }
+
+    #[test]
+    fn test_input_into_not_allocated() {
+        let hint_processor =
+            JuvixHintProcessor::new(ProgramInput::from_json(r#"{"X": 7}"#).unwrap());
+        let (mut vm, ids_data) = setup_vm(&[("p", Relocatable::from((0, 0)).into())]);
+        vm.set_ap(0);
+        assert!(hint_processor
+            .execute(
+                &mut vm,
+                &mut ExecutionScopes::new(),
+                &Hint::InputInto("X".into()),
+                &ids_data,
+                &ApTracking::new(),
+                &HashMap::new(),
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn test_input_into_missing() {
+        let hint_processor = JuvixHintProcessor::new(ProgramInput::from_json(r#"{}"#).unwrap());
+        let (mut vm, ids_data) = setup_vm(&[]);
+        assert!(matches!(
+            hint_processor.execute(
+                &mut vm,
+                &mut ExecutionScopes::new(),
+                &Hint::InputInto("X".into()),
+                &ids_data,
+                &ApTracking::new(),
+                &HashMap::new(),
+            ),
+            Err(HintError::CustomHint(msg)) if msg.as_ref() == "InputInto(X): there is no input X"
+        ));
+    }
+
+    #[rstest]
+    #[case(Felt252::from(3), 3)]
+    #[case(Felt252::ZERO - Felt252::from(3), -3)]
+    #[case(Felt252::MAX, -1)]
+    fn test_felt_to_signed(#[case] felt: Felt252, #[case] expected: i64) {
+        assert_eq!(felt_to_signed(&felt), BigInt::from(expected));
+    }
+
+    #[rstest]
+    #[case(1, 2, true)]
+    #[case(2, 1, false)]
+    #[case(2, 2, false)]
+    #[case(-1, 1, true)]
+    #[case(1, -1, false)]
+    #[case(-5, -2, true)]
+    #[case(-2, -5, false)]
+    fn test_signed_lt(#[case] a: i64, #[case] b: i64, #[case] expected: bool) {
+        let (mut vm, ids_data) = setup_vm(&[
+            ("a", Felt252::from(a).into()),
+            ("b", Felt252::from(b).into()),
+        ]);
+        execute_hint(&mut vm, &ids_data, &Hint::SignedLt("a".into(), "b".into())).unwrap();
+        assert_eq!(
+            *vm.get_integer(vm.get_ap()).unwrap(),
+            Felt252::from(expected as u8)
+        );
+    }
+
+    #[rstest]
+    #[case(Felt252::from(0), Felt252::from(10), 1)]
+    #[case(Felt252::from(9), Felt252::from(10), 1)]
+    #[case(Felt252::from(10), Felt252::from(10), 0)]
+    #[case(Felt252::from(11), Felt252::from(10), 0)]
+    #[case(Felt252::from(0), Felt252::from(0), 0)]
+    // -1 is the largest field element, not a negative number
+    #[case(Felt252::from(-1), Felt252::from(10), 0)]
+    #[case(Felt252::from(10), Felt252::MAX, 1)]
+    fn test_range_check(#[case] value: Felt252, #[case] bound: Felt252, #[case] expected: u8) {
+        let (mut vm, ids_data) = setup_vm(&[("x", value.into()), ("bound", bound.into())]);
+        execute_hint(
+            &mut vm,
+            &ids_data,
+            &Hint::RangeCheck("x".into(), "bound".into()),
+        )
+        .unwrap();
+        assert_eq!(
+            *vm.get_integer(vm.get_ap()).unwrap(),
+            Felt252::from(expected)
+        );
+    }
+
+    #[rstest]
+    #[case(Felt252::ZERO, 0)]
+    #[case(Felt252::from(0b1011_0110), 5)]
+    #[case(Felt252::from(u128::MAX), 128)]
+    // 2^251 + 17 * 2^192
+    #[case(Felt252::MAX, 3)]
+    // 2^251 + 16 * 2^192 + (2^192 - 1)
+    #[case(Felt252::ZERO - Felt252::from(2), 194)]
+    fn test_pop_count(#[case] value: Felt252, #[case] expected: u32) {
+        let (mut vm, ids_data) = setup_vm(&[("x", value.into())]);
+        execute_hint(&mut vm, &ids_data, &Hint::PopCount("x".into())).unwrap();
+        assert_eq!(
+            *vm.get_integer(vm.get_ap()).unwrap(),
+            Felt252::from(expected)
+        );
+    }
+
+    #[test]
+    fn test_step_count() {
+        let mut hint_processor = JuvixHintProcessor::new(ProgramInput::new(HashMap::new()));
+        for _ in 0..3 {
+            hint_processor.consume_step();
+        }
+        let (mut vm, ids_data) = setup_vm(&[]);
+        hint_processor
+            .execute(
+                &mut vm,
+                &mut ExecutionScopes::new(),
+                &Hint::StepCount,
+                &ids_data,
+                &ApTracking::new(),
+                &HashMap::new(),
+            )
+            .unwrap();
+        assert_eq!(*vm.get_integer(vm.get_ap()).unwrap(), Felt252::from(3));
+        hint_processor.reset();
+        assert_eq!(hint_processor.steps, 0);
+    }
+
+    #[rstest]
+    #[case(r#"{"X": []}"#, &[])]
+    #[case(r#"{"X": [104, 101, 108, 108, 111]}"#, &["0x68656c6c6f"])]
+    #[case(
+        r#"{"X": [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 255]}"#,
+        &["0x000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e", "0xff"]
+    )]
+    fn test_pack_bytes(#[case] input: &str, #[case] chunks: &[&str]) {
+        let hint_processor = JuvixHintProcessor::new(ProgramInput::from_json(input).unwrap());
+        let (mut vm, ids_data) = setup_vm(&[]);
+        hint_processor
+            .execute(
+                &mut vm,
+                &mut ExecutionScopes::new(),
+                &Hint::PackBytes("X".into()),
+                &ids_data,
+                &ApTracking::new(),
+                &HashMap::new(),
+            )
+            .unwrap();
+        let addr = vm.get_relocatable(vm.get_ap()).unwrap();
+        let len = match hint_processor.program_input.get("X") {
+            Value::ValueList(elems) => elems.len(),
+            _ => unreachable!(),
+        };
+        assert_eq!(*vm.get_integer(addr).unwrap(), Felt252::from(len));
+        for (i, chunk) in chunks.iter().enumerate() {
+            assert_eq!(
+                *vm.get_integer((addr + (i + 1)).unwrap()).unwrap(),
+                Felt252::from_hex(chunk).unwrap()
+            );
+        }
+        assert!(vm
+            .get_maybe(&(addr + (chunks.len() + 1)).unwrap())
+            .is_none());
+    }
+
+    #[rstest]
+    #[case(r#"{"X": [1, 256]}"#)]
+    #[case(r#"{"X": [1, true]}"#)]
+    #[case(r#"{"X": 7}"#)]
+    #[case(r#"{"Y": [1]}"#)]
+    fn test_pack_bytes_invalid(#[case] input: &str) {
+        let hint_processor = JuvixHintProcessor::new(ProgramInput::from_json(input).unwrap());
+        let (mut vm, ids_data) = setup_vm(&[]);
+        assert!(hint_processor
+            .execute(
+                &mut vm,
+                &mut ExecutionScopes::new(),
+                &Hint::PackBytes("X".into()),
+                &ids_data,
+                &ApTracking::new(),
+                &HashMap::new(),
+            )
+            .is_err());
+    }
+
+    #[rstest]
+    #[case(r#"{"A": [1, 2], "B": [3, 4]}"#, r#"{"X": [1, 2, 3, 4]}"#)]
+    #[case(r#"{"A": [], "B": [true]}"#, r#"{"X": [true]}"#)]
+    #[case(r#"{"A": [{"a": 1}], "B": []}"#, r#"{"X": [{"a": 1}]}"#)]
+    fn test_concat(#[case] input: &str, #[case] expected: &str) {
+        let hint_processor = JuvixHintProcessor::new(ProgramInput::from_json(input).unwrap());
+        let (mut vm, ids_data) = setup_vm(&[]);
+        hint_processor
+            .execute(
+                &mut vm,
+                &mut ExecutionScopes::new(),
+                &Hint::Concat("A".into(), "B".into()),
+                &ids_data,
+                &ApTracking::new(),
+                &HashMap::new(),
+            )
+            .unwrap();
+        let expected = ProgramInput::from_json(expected).unwrap();
+        let expected = expected.get("X");
+        assert_eq!(decode_value(&vm, vm.get_ap(), expected), *expected);
+    }
+
+    #[test]
+    fn test_concat_not_list() {
+        let hint_processor =
+            JuvixHintProcessor::new(ProgramInput::from_json(r#"{"A": [1], "B": 2}"#).unwrap());
+        let (mut vm, ids_data) = setup_vm(&[]);
+        assert!(hint_processor
+            .execute(
+                &mut vm,
+                &mut ExecutionScopes::new(),
+                &Hint::Concat("A".into(), "B".into()),
+                &ids_data,
+                &ApTracking::new(),
+                &HashMap::new(),
+            )
+            .is_err());
+    }
+
+    #[rstest]
+    #[case(r#"{"B": [1]}"#, "A")]
+    #[case(r#"{"A": [1]}"#, "B")]
+    fn test_concat_missing(#[case] input: &str, #[case] missing: &str) {
+        let hint_processor = JuvixHintProcessor::new(ProgramInput::from_json(input).unwrap());
+        let (mut vm, ids_data) = setup_vm(&[]);
+        let result = hint_processor.execute(
+            &mut vm,
+            &mut ExecutionScopes::new(),
+            &Hint::Concat("A".into(), "B".into()),
+            &ids_data,
+            &ApTracking::new(),
+            &HashMap::new(),
+        );
+        assert!(matches!(
+            result,
+            Err(HintError::CustomHint(msg))
+                if *msg == format!("Concat(A, B): there is no input {missing}")
+        ));
+    }
+
+    #[rstest]
+    #[case(r#"{"X": [1, 2, 3]}"#, Some(r#"[3, 2, 1]"#))]
+    #[case(r#"{"X": []}"#, Some(r#"[]"#))]
+    #[case(r#"{"X": [{"a": 1}, [true]]}"#, Some(r#"[[true], {"a": 1}]"#))]
+    #[case(r#"{"X": 1}"#, None)]
+    #[case(r#"{"Y": [1]}"#, None)]
+    fn test_reverse(#[case] input: &str, #[case] expected: Option<&str>) {
+        let hint_processor = JuvixHintProcessor::new(ProgramInput::from_json(input).unwrap());
+        let (mut vm, ids_data) = setup_vm(&[]);
+        let result = hint_processor.execute(
+            &mut vm,
+            &mut ExecutionScopes::new(),
+            &Hint::Reverse("X".into()),
+            &ids_data,
+            &ApTracking::new(),
+            &HashMap::new(),
+        );
+        match expected {
+            Some(expected) => {
+                result.unwrap();
+                let expected = ProgramInput::from_json(&format!(r#"{{"X": {expected}}}"#)).unwrap();
+                let expected = expected.get("X");
+                assert_eq!(decode_value(&vm, vm.get_ap(), expected), *expected);
+            }
+            None => assert!(result.is_err()),
+        }
+    }
+
+    #[rstest]
+    #[case(r#"{"X": [1, 2, 3]}"#, 10, Some(r#"[11, 12, 13]"#))]
+    #[case(r#"{"X": []}"#, 10, Some(r#"[]"#))]
+    #[case(r#"{"X": [5]}"#, -5, Some(r#"[0]"#))]
+    #[case(r#"{"X": [1, [2]]}"#, 10, None)]
+    #[case(r#"{"X": 1}"#, 10, None)]
+    #[case(r#"{"Y": [1]}"#, 10, None)]
+    fn test_map_increment(#[case] input: &str, #[case] delta: i64, #[case] expected: Option<&str>) {
+        let hint_processor = JuvixHintProcessor::new(ProgramInput::from_json(input).unwrap());
+        let (mut vm, ids_data) = setup_vm(&[("d", Felt252::from(delta).into())]);
+        let result = hint_processor.execute(
+            &mut vm,
+            &mut ExecutionScopes::new(),
+            &Hint::MapIncrement("X".into(), "d".into()),
+            &ids_data,
+            &ApTracking::new(),
+            &HashMap::new(),
+        );
+        match expected {
+            Some(expected) => {
+                result.unwrap();
+                let expected = ProgramInput::from_json(&format!(r#"{{"X": {expected}}}"#)).unwrap();
+                let expected = expected.get("X");
+                assert_eq!(decode_value(&vm, vm.get_ap(), expected), *expected);
+            }
+            None => assert!(result.is_err()),
+        }
+    }
+
+    #[rstest]
+    #[case(r#"{"A": 1, "B": 1}"#, true)]
+    #[case(r#"{"A": 1, "B": 2}"#, false)]
+    #[case(r#"{"A": {"x": 1, "y": [true]}, "B": {"x": 1, "y": [true]}}"#, true)]
+    #[case(r#"{"A": {"x": 1, "y": [true]}, "B": {"x": 1, "y": [false]}}"#, false)]
+    #[case(r#"{"A": {"x": 1}, "B": [1]}"#, false)]
+    fn test_assert_input_eq(#[case] input: &str, #[case] equal: bool) {
+        let hint_processor = JuvixHintProcessor::new(ProgramInput::from_json(input).unwrap());
+        let (mut vm, ids_data) = setup_vm(&[]);
+        let result = hint_processor.execute(
+            &mut vm,
+            &mut ExecutionScopes::new(),
+            &Hint::AssertInputEq("A".into(), "B".into()),
+            &ids_data,
+            &ApTracking::new(),
+            &HashMap::new(),
+        );
+        match result {
+            Ok(()) => assert!(equal),
+            Err(HintError::CustomHint(msg)) => {
+                assert!(!equal);
+                assert!(msg.contains("A and B"));
+            }
+            Err(e) => panic!("unexpected error: {e}"),
+        }
+        // nothing is written
+        assert!(vm.get_maybe(&vm.get_ap()).is_none());
+    }
+
+    #[rstest]
+    #[case(r#"{"X": [1, 2, 3]}"#, 3, None)]
+    #[case(r#"{"X": []}"#, 0, None)]
+    #[case(r#"{"X": [1, 2]}"#, 3, Some("has 2 elements, expected 3"))]
+    #[case(r#"{"X": [1, 2, 3, 4]}"#, 3, Some("has 4 elements, expected 3"))]
+    #[case(r#"{"X": 3}"#, 3, Some("not a list"))]
+    #[case(r#"{"Y": [1, 2, 3]}"#, 3, Some("there is no input X"))]
+    fn test_assert_len(#[case] input: &str, #[case] len: usize, #[case] error: Option<&str>) {
+        let hint_processor = JuvixHintProcessor::new(ProgramInput::from_json(input).unwrap());
+        let (mut vm, ids_data) = setup_vm(&[]);
+        let result = hint_processor.execute(
+            &mut vm,
+            &mut ExecutionScopes::new(),
+            &Hint::AssertLen("X".into(), len),
+            &ids_data,
+            &ApTracking::new(),
+            &HashMap::new(),
+        );
+        match (result, error) {
+            (Ok(()), None) => (),
+            (Err(HintError::CustomHint(msg)), Some(error)) => {
+                assert!(msg.starts_with(&format!("AssertLen(X, {len})")));
+                assert!(msg.contains(error));
+            }
+            (result, _) => panic!("unexpected result: {result:?}"),
+        }
+        // nothing is written
+        assert!(vm.get_maybe(&vm.get_ap()).is_none());
+    }
+
+    #[rstest]
+    #[case(Felt252::ONE, "A")]
+    #[case(Felt252::from(-1), "A")]
+    #[case(Felt252::ZERO, "B")]
+    fn test_select(#[case] cond: Felt252, #[case] selected: &str) {
+        let hint_processor = JuvixHintProcessor::new(
+            ProgramInput::from_json(r#"{"A": [1, 2], "B": {"x": 3}}"#).unwrap(),
+        );
+        let (mut vm, ids_data) = setup_vm(&[("c", cond.into())]);
+        hint_processor
+            .execute(
+                &mut vm,
+                &mut ExecutionScopes::new(),
+                &Hint::Select("c".into(), "A".into(), "B".into()),
+                &ids_data,
+                &ApTracking::new(),
+                &HashMap::new(),
+            )
+            .unwrap();
+        let val = hint_processor.program_input.get(selected);
+        assert_eq!(decode_value(&vm, vm.get_ap(), val), *val);
+    }
+
+    #[test]
+    fn test_constant_input() {
+        let hint_processor =
+            JuvixHintProcessor::new(ProgramInput::from_json(r#"{"X": 42, "N": 1}"#).unwrap());
+        let constants = HashMap::from([
+            ("__main__.N".to_string(), Felt252::from(7)),
+            ("__main__.M".to_string(), Felt252::from(42)),
+            ("__main__.K".to_string(), Felt252::from(3)),
+            ("lib.K".to_string(), Felt252::from(4)),
+        ]);
+        let (mut vm, ids_data) = setup_vm(&[]);
+        let mut execute = |hint: Hint| {
+            hint_processor.execute(
+                &mut vm,
+                &mut ExecutionScopes::new(),
+                &hint,
+                &ids_data,
+                &ApTracking::new(),
+                &constants,
+            )
+        };
+        execute(Hint::AssertInputEq("X".into(), "M".into())).unwrap();
+        // the input shadows the constant
+        assert!(execute(Hint::AssertInputEq("N".into(), "M".into())).is_err());
+        assert!(execute(Hint::Input("K".into())).is_err());
+        assert!(execute(Hint::Input("L".into())).is_err());
+        execute(Hint::Input("M".into())).unwrap();
+        assert_eq!(*vm.get_integer(vm.get_ap()).unwrap(), Felt252::from(42));
+    }
+
+    #[rstest]
+    #[case(r#"{"X": 7}"#)]
+    #[case(r#"{"X": [1, {"A": 2, "B": [true]}]}"#)]
+    #[case(r#"{"X": {"A": {"str": "hello"}, "B": [[], [false]], "C": 3}}"#)]
+    fn test_flatten_matches_encoding(#[case] input: &str) {
+        let program_input = ProgramInput::from_json(input).unwrap();
+        let val = program_input.get("X");
+        let flat = program_input.flatten_var("X").unwrap();
+        assert_eq!(flat.len(), encoded_size(val));
+
+        let hint_processor = JuvixHintProcessor::new(program_input.clone());
+        let mut vm = VirtualMachine::new(false);
+        let addr = vm.add_memory_segment();
+        hint_processor.read_value_input(&mut vm, addr, val).unwrap();
+        for (i, cell) in flat.iter().enumerate() {
+            let expected = match vm.get_maybe(&(addr + i).unwrap()).unwrap() {
+                MaybeRelocatable::Int(felt) => felt,
+                MaybeRelocatable::RelocatableValue(ptr) => {
+                    assert_eq!(ptr.segment_index, addr.segment_index);
+                    Felt252::from(ptr.offset)
+                }
+            };
+            assert_eq!(*cell, expected);
+        }
+    }
+
+    #[rstest]
+    #[case(0, Some(r#"{"X": 5}"#))]
+    #[case(1, Some(r#"{"X": {"a": 1, "b": [true]}}"#))]
+    #[case(2, Some(r#"{"X": [1, 2]}"#))]
+    #[case(3, None)]
+    fn test_input_index(#[case] index: u64, #[case] expected: Option<&str>) {
+        let hint_processor = JuvixHintProcessor::new(
+            ProgramInput::from_json(r#"{"X": [5, {"a": 1, "b": [true]}, [1, 2]]}"#).unwrap(),
+        );
+        let (mut vm, ids_data) = setup_vm(&[("i", Felt252::from(index).into())]);
+        let result = hint_processor.execute(
+            &mut vm,
+            &mut ExecutionScopes::new(),
+            &Hint::InputIndex("X".into(), "i".into()),
+            &ids_data,
+            &ApTracking::new(),
+            &HashMap::new(),
+        );
+        match expected {
+            Some(expected) => {
+                result.unwrap();
+                let expected = ProgramInput::from_json(expected).unwrap();
+                let expected = expected.get("X");
+                assert_eq!(decode_value(&vm, vm.get_ap(), expected), *expected);
+            }
+            None => assert!(result.is_err()),
+        }
+    }
+
+    #[test]
+    fn test_input_index_missing() {
+        let hint_processor =
+            JuvixHintProcessor::new(ProgramInput::from_json(r#"{"Y": [1]}"#).unwrap());
+        let (mut vm, ids_data) = setup_vm(&[("i", Felt252::ZERO.into())]);
+        assert!(matches!(
+            hint_processor.execute(
+                &mut vm,
+                &mut ExecutionScopes::new(),
+                &Hint::InputIndex("X".into(), "i".into()),
+                &ids_data,
+                &ApTracking::new(),
+                &HashMap::new(),
+            ),
+            Err(HintError::CustomHint(msg)) if msg.as_ref() == "InputIndex(X): there is no input X"
+        ));
+    }
+
+    #[test]
+    fn test_interrupt() {
+        static FLAG: AtomicBool = AtomicBool::new(false);
+        let program_content = std::fs::read("tests/fibonacci.json").unwrap();
+        let cairo_run_config = cairo_vm::cairo_run::CairoRunConfig::default();
+        let mut hint_processor =
+            JuvixHintProcessor::new(ProgramInput::new(HashMap::new())).with_interrupt(&FLAG);
+        assert!(cairo_vm::cairo_run::cairo_run(
+            &program_content,
+            &cairo_run_config,
+            &mut hint_processor
+        )
+        .is_ok());
+        FLAG.store(true, Ordering::SeqCst);
+        assert!(hint_processor.interrupted());
+        assert!(cairo_vm::cairo_run::cairo_run(
+            &program_content,
+            &cairo_run_config,
+            &mut hint_processor
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_keccak_out_of_bounds() {
+        let (mut vm, ids_data) = setup_vm(&[
+            ("ptr", Relocatable::from((2, 0)).into()),
+            ("len", Felt252::from(2).into()),
+            ("out", Relocatable::from((3, 0)).into()),
+        ]);
+        let input_ptr = vm.add_memory_segment();
+        vm.add_memory_segment();
+        vm.insert_value(input_ptr, Felt252::from(1)).unwrap();
+        assert!(execute_hint(
+            &mut vm,
+            &ids_data,
+            &Hint::Keccak("ptr".into(), "len".into(), "out".into()),
+        )
+        .is_err());
+    }
+}
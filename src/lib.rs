@@ -1,17 +1,46 @@
 #![forbid(unsafe_code)]
+use base64ct::{Base64, Encoding};
 use bincode::enc::write::Writer;
 use cairo_vm::air_public_input::PublicInputError;
 use cairo_vm::cairo_run::{self, EncodeTraceError};
+use cairo_vm::hint_processor::hint_processor_utils::felt_to_usize;
+use cairo_vm::serde::deserialize_program::{deserialize_program_json, ProgramJson};
+use cairo_vm::types::errors::program_errors::ProgramError;
+use cairo_vm::types::instruction::{ApUpdate, Opcode, PcUpdate};
+use cairo_vm::types::program::Program;
+use cairo_vm::types::relocatable::{MaybeRelocatable, Relocatable};
+use cairo_vm::vm::decoding::decoder::decode_instruction;
 use cairo_vm::vm::errors::cairo_run_errors::CairoRunError;
+use cairo_vm::vm::errors::runner_errors::RunnerError;
 use cairo_vm::vm::errors::trace_errors::TraceError;
 use cairo_vm::vm::errors::vm_errors::VirtualMachineError;
-use clap::{Parser, ValueHint};
-use juvix_hint_processor::hint_processor::JuvixHintProcessor;
-use program_input::ProgramInput;
-use std::collections::HashMap;
+use cairo_vm::vm::errors::vm_exception::VmException;
+use cairo_vm::vm::runners::builtin_runner::BuiltinRunner;
+use cairo_vm::vm::runners::cairo_pie::CairoPie;
+use cairo_vm::vm::runners::cairo_runner::CairoRunner;
+use cairo_vm::vm::security::verify_secure_runner;
+use cairo_vm::vm::trace::trace_entry::RelocatedTraceEntry;
+use cairo_vm::vm::vm_core::VirtualMachine;
+use cairo_vm::Felt252;
+use clap::{Parser, ValueEnum, ValueHint};
+pub use config::{parse_args, Config};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+pub use juvix_hint_processor::hint::Hint;
+use juvix_hint_processor::hint_processor::{felt_to_signed, JuvixHintProcessor};
+use num_bigint::BigUint;
+use program_input::{FeltInputValidation, InputFormat, InputOptions, ProgramInput};
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Keccak256};
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
 use std::io::{self, Write};
 use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 use thiserror::Error;
+use zip::write::FileOptions;
+use zip::{CompressionMethod, ZipWriter};
 
 #[cfg(feature = "with_mimalloc")]
 use mimalloc::MiMalloc;
@@ -22,23 +51,73 @@ static ALLOC: MiMalloc = MiMalloc;
 
 pub mod program_input;
 
+mod config;
+mod interrupt;
 mod juvix_hint_processor;
+mod progress;
+mod warning;
 
-#[derive(Parser, Debug)]
+#[derive(Parser, Debug, Clone)]
 #[clap(author, version, about, long_about = None)]
 pub struct Args {
     #[clap(value_parser, value_hint=ValueHint::FilePath)]
     pub filename: PathBuf,
     #[clap(long = "program_input", value_parser, value_hint=ValueHint::FilePath)]
     pub program_input: Option<PathBuf>,
+    #[clap(long = "program_input_base64", conflicts_with = "program_input")]
+    pub program_input_base64: Option<String>,
+    #[clap(long = "program_input_ns", value_parser = parse_input_namespace)]
+    pub program_input_ns: Vec<(String, PathBuf)>,
+    #[clap(long = "input_schema", value_parser, value_hint=ValueHint::FilePath)]
+    pub input_schema: Option<PathBuf>,
+    #[clap(long = "input_format", value_enum, default_value_t = InputFormat::Json)]
+    pub input_format: InputFormat,
+    #[clap(
+        long = "felt_input_validation",
+        value_enum,
+        default_value_t = FeltInputValidation::Strict
+    )]
+    pub felt_input_validation: FeltInputValidation,
+    // Replaces the field prime as the bound of the input integers under
+    // strict validation, for experimenting with smaller fields
+    #[clap(long = "input_range_modulus", value_parser = parse_range_modulus)]
+    pub input_range_modulus: Option<BigUint>,
+    #[structopt(long = "resolve_env")]
+    pub resolve_env: bool,
     #[clap(long = "trace_file", value_parser)]
     pub trace_file: Option<PathBuf>,
     #[structopt(long = "print_output")]
     pub print_output: bool,
+    #[clap(
+        long = "felt_output_format",
+        alias = "output_radix",
+        value_enum,
+        default_value_t = FeltOutputFormat::Dec
+    )]
+    pub felt_output_format: FeltOutputFormat,
+    #[clap(long = "emit_output_felts", value_parser)]
+    pub emit_output_felts: Option<PathBuf>,
     #[structopt(long = "entrypoint", default_value = "main")]
     pub entrypoint: String,
     #[structopt(long = "memory_file")]
     pub memory_file: Option<PathBuf>,
+    #[clap(long = "memory_holes_json", value_parser)]
+    pub memory_holes_json: Option<PathBuf>,
+    #[clap(long = "trace_format", value_enum, default_value_t = ArtifactFormat::Binary)]
+    pub trace_format: ArtifactFormat,
+    // Adds the opcode of the instruction executed at each step to a JSON
+    // trace. The other trace formats are read by provers, so they are left
+    // alone.
+    #[structopt(long = "trace_include_registers")]
+    pub trace_include_registers: bool,
+    #[clap(long = "memory_format", value_enum, default_value_t = MemoryFormat::Binary)]
+    pub memory_format: MemoryFormat,
+    #[structopt(long = "compress_artifacts")]
+    pub compress_artifacts: bool,
+    #[clap(long = "trace_buffer_size", default_value_t = 3 * 1024 * 1024)]
+    pub trace_buffer_size: usize,
+    #[clap(long = "memory_buffer_size", default_value_t = 5 * 1024 * 1024)]
+    pub memory_buffer_size: usize,
     #[clap(long = "layout", default_value = "plain", value_parser=validate_layout)]
     pub layout: String,
     #[structopt(long = "proof_mode")]
@@ -47,10 +126,10 @@ pub struct Args {
     pub secure_run: Option<bool>,
     #[clap(long = "air_public_input", requires = "proof_mode")]
     pub air_public_input: Option<String>,
-    #[clap(
-        long = "air_private_input",
-        requires_all = ["proof_mode", "trace_file", "memory_file"]
-    )]
+    // Without --trace_file or --memory_file, the trace or memory is written
+    // next to the AIR private input, with `.trace` or `.memory` appended to its
+    // path, for the private input to refer to.
+    #[clap(long = "air_private_input", requires = "proof_mode")]
     pub air_private_input: Option<String>,
     #[clap(
         long = "cairo_pie_output",
@@ -59,23 +138,232 @@ pub struct Args {
         conflicts_with_all = ["proof_mode", "air_private_input", "air_public_input"]
     )]
     pub cairo_pie_output: Option<String>,
+    #[clap(long = "manifest", value_parser)]
+    pub manifest: Option<PathBuf>,
+    #[structopt(long = "manifest_hashes", requires = "manifest")]
+    pub manifest_hashes: bool,
+    #[clap(
+        long = "cairo_pie_compression",
+        value_enum,
+        default_value_t = PieCompression::Default
+    )]
+    pub cairo_pie_compression: PieCompression,
     #[structopt(long = "allow_missing_builtins")]
     pub allow_missing_builtins: Option<bool>,
+    #[clap(
+        long = "no_output_on_error",
+        default_value_t = true,
+        num_args = 0..=1,
+        default_missing_value = "true",
+        action = clap::ArgAction::Set
+    )]
+    pub no_output_on_error: bool,
+    #[structopt(long = "best_effort")]
+    pub best_effort: bool,
+    #[structopt(long = "handle_interrupt")]
+    pub handle_interrupt: bool,
+    // Shows the steps executed so far on stderr, if it is a terminal
+    #[structopt(long = "progress")]
+    pub progress: bool,
+    #[clap(long = "max_memory_cells")]
+    pub max_memory_cells: Option<usize>,
+    // The maximum number of bytes of formatted output kept; the rest is cut off
+    #[clap(long = "output_cap")]
+    pub output_cap: Option<usize>,
+    #[structopt(long = "builtin_hints")]
+    pub builtin_hints: bool,
+    #[structopt(long = "strict_alloc")]
+    pub strict_alloc: bool,
+    // Seeds the RNG of the RandomEcPoint hint, which otherwise uses a fixed seed
+    #[clap(long = "seed")]
+    pub seed: Option<u64>,
+    #[structopt(long = "require_hints")]
+    pub require_hints: bool,
+    #[structopt(long = "fail_on_warnings")]
+    pub fail_on_warnings: bool,
+    #[structopt(long = "warn_unfilled_output")]
+    pub warn_unfilled_output: bool,
+    #[clap(long = "error_format", value_enum, default_value_t = ErrorFormat::Text)]
+    pub error_format: ErrorFormat,
+    #[clap(long = "repeat", default_value_t = 1, value_parser = validate_repeat)]
+    pub repeat: usize,
+    #[structopt(long = "program_info")]
+    pub program_info: bool,
+    #[structopt(long = "print_program_hash")]
+    pub print_program_hash: bool,
+    #[structopt(long = "list_hints")]
+    pub list_hints: bool,
+    #[structopt(long = "dry_run_hints")]
+    pub dry_run_hints: bool,
+    #[structopt(long = "entrypoint_list")]
+    pub entrypoint_list: bool,
+    #[structopt(long = "print_trace_summary")]
+    pub print_trace_summary: bool,
+    #[structopt(long = "print_builtin_counts")]
+    pub print_builtin_counts: bool,
+    #[structopt(long = "benchmark_layouts")]
+    pub benchmark_layouts: bool,
+    #[clap(long = "config", value_parser, value_hint=ValueHint::FilePath)]
+    pub config: Option<PathBuf>,
+}
+
+/// How the output felts are rendered when printed or emitted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FeltOutputFormat {
+    /// Signed decimal, as printed by the Cairo VM
+    #[default]
+    Dec,
+    /// `0x`-prefixed hexadecimal
+    Hex,
+    /// 32 little-endian bytes in hexadecimal
+    #[value(name = "bytes_le")]
+    BytesLe,
+}
+
+/// How the CLI reports the error that ended a run.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorFormat {
+    /// Free-form text
+    #[default]
+    Text,
+    /// A JSON object with the error message and kind
+    Json,
+}
+
+/// The encoding of the trace file.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ArtifactFormat {
+    /// The binary encoding read by the Stone prover
+    #[default]
+    Binary,
+    /// MessagePack `[pc, ap, fp]` entries
+    Msgpack,
+    /// A JSON array of `{"pc", "ap", "fp"}` objects
+    Json,
+}
+
+/// The encoding of the memory file.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MemoryFormat {
+    /// The binary encoding read by the Stone prover
+    #[default]
+    Binary,
+    /// MessagePack `[address, value]` pairs
+    Msgpack,
+    /// The binary encoding preceded by a header with the magic `JCVM`, the
+    /// format version, the number of entries and the word size in bytes
+    V2,
+}
+
+/// How the files of the Cairo PIE zip archive are compressed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PieCompression {
+    /// No compression
+    Store,
+    /// Deflate, favouring speed
+    Fast,
+    /// Deflate at its default level
+    #[default]
+    Default,
+    /// Deflate, favouring size
+    Best,
+}
+
+impl PieCompression {
+    fn file_options(self) -> FileOptions {
+        let deflated = FileOptions::default().compression_method(CompressionMethod::Deflated);
+        match self {
+            PieCompression::Store => {
+                FileOptions::default().compression_method(CompressionMethod::Stored)
+            }
+            PieCompression::Fast => deflated.compression_level(Some(1)),
+            PieCompression::Default => deflated,
+            PieCompression::Best => deflated.compression_level(Some(9)),
+        }
+    }
+}
+
+// The header of the v2 memory format
+const MEMORY_V2_MAGIC: &[u8; 4] = b"JCVM";
+const MEMORY_V2_VERSION: u32 = 2;
+const MEMORY_V2_WORD_SIZE: u32 = 32;
+
+// Renders an output felt in the given format.
+fn format_felt(felt: &Felt252, format: FeltOutputFormat) -> String {
+    match format {
+        FeltOutputFormat::Dec => felt_to_signed(felt).to_string(),
+        FeltOutputFormat::Hex => format!("{:#x}", felt.to_biguint()),
+        FeltOutputFormat::BytesLe => felt
+            .to_bytes_le()
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect(),
+    }
+}
+
+// Splits a `prefix=path` argument of --program_input_ns.
+pub(crate) fn parse_input_namespace(value: &str) -> Result<(String, PathBuf), String> {
+    let (prefix, path) = value
+        .split_once('=')
+        .ok_or_else(|| format!("{value} is not of the form <prefix>=<path>"))?;
+    let valid_part = |part: &str| {
+        part.chars()
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+            && part.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+    };
+    if !prefix.split('.').all(valid_part) {
+        return Err(format!("{prefix} is not a valid input namespace"));
+    }
+    Ok((prefix.to_string(), PathBuf::from(path)))
+}
+
+fn validate_repeat(value: &str) -> Result<usize, String> {
+    match value.parse::<usize>() {
+        Ok(n) if n > 0 => Ok(n),
+        _ => Err(format!("{value} is not a positive number of runs")),
+    }
+}
+
+const LAYOUTS: [&str; 10] = [
+    "plain",
+    "small",
+    "dex",
+    "recursive",
+    "starknet",
+    "starknet_with_keccak",
+    "recursive_large_output",
+    "all_cairo",
+    "all_solidity",
+    "dynamic",
+];
+
+// Parses an --input_range_modulus, a decimal or `0x` hexadecimal integer
+// greater than 1 and at most the field prime.
+pub(crate) fn parse_range_modulus(value: &str) -> Result<BigUint, String> {
+    let modulus = match value.strip_prefix("0x") {
+        Some(digits) => BigUint::parse_bytes(digits.as_bytes(), 16),
+        None => BigUint::parse_bytes(value.as_bytes(), 10),
+    }
+    .ok_or_else(|| format!("{value} is not an integer"))?;
+    if modulus < BigUint::from(2u32) || modulus > program_input::field_prime() {
+        return Err(format!(
+            "the modulus must be greater than 1 and at most the field prime, got {value}"
+        ));
+    }
+    Ok(modulus)
 }
 
 fn validate_layout(value: &str) -> Result<String, String> {
-    match value {
-        "plain"
-        | "small"
-        | "dex"
-        | "recursive"
-        | "starknet"
-        | "starknet_with_keccak"
-        | "recursive_large_output"
-        | "all_cairo"
-        | "all_solidity"
-        | "dynamic" => Ok(value.to_string()),
-        _ => Err(format!("{value} is not a valid layout")),
+    if LAYOUTS.contains(&value) {
+        Ok(value.to_string())
+    } else {
+        Err(format!("{value} is not a valid layout"))
     }
 }
 
@@ -83,10 +371,48 @@ fn validate_layout(value: &str) -> Result<String, String> {
 pub enum Error {
     #[error("Invalid arguments")]
     Cli(#[from] clap::Error),
+    #[error("Invalid config file: {0}")]
+    Config(String),
     #[error("Failed to interact with the file system")]
     IO(#[from] std::io::Error),
+    #[error("The program input file {} is empty", .0.display())]
+    EmptyProgramInput(PathBuf),
+    #[error("The program input variable {0} is given more than once")]
+    DuplicateInputKey(String),
+    #[error("Invalid base64 program input: {0}")]
+    ProgramInputBase64(String),
+    #[error("The program input does not match the schema: {0}")]
+    InputSchema(String),
+    #[error(
+        "The layout {layout} does not provide the builtin(s) {builtins} used by the program; \
+         pass --allow_missing_builtins or choose a larger layout such as all_cairo"
+    )]
+    MissingBuiltins { builtins: String, layout: String },
+    #[error("Invalid hint: {0}")]
+    Hint(String),
+    #[error("The program has no main function; pass one of --entrypoint {}", .0.join(", "))]
+    AmbiguousEntrypoint(Vec<String>),
+    #[error(
+        "The program has no Juvix hints although --require_hints was given; \
+         it was probably compiled without the hints it needs to read its input"
+    )]
+    NoHints,
+    #[error("The run issued {} warning(s): {}", .0.len(), .0.join("; "))]
+    Warnings(Vec<String>),
+    #[error("The program has {0} unsupported hints")]
+    UnsupportedHints(usize),
+    #[error("The cairo program execution was interrupted")]
+    Interrupted,
+    #[error("The program used {used} memory cells, more than the limit of {limit}")]
+    MemoryLimitExceeded { used: usize, limit: usize },
     #[error("The cairo program execution failed")]
     Runner(#[from] CairoRunError),
+    #[error("The cairo program execution failed after writing some output")]
+    PartialOutput {
+        output: String,
+        // boxed, as the run error would make every `Error` as large as it is
+        error: Box<CairoRunError>,
+    },
     #[error(transparent)]
     EncodeTrace(#[from] EncodeTraceError),
     #[error(transparent)]
@@ -97,10 +423,142 @@ pub enum Error {
     PublicInput(#[from] PublicInputError),
     #[error(transparent)]
     PrivateInput(#[from] serde_json::Error),
+    #[error(transparent)]
+    Program(#[from] ProgramError),
+    #[error(transparent)]
+    Msgpack(#[from] rmp_serde::encode::Error),
+    #[error(transparent)]
+    Zip(#[from] zip::result::ZipError),
+}
+
+impl Error {
+    // The name of the variant, reported as the error kind by --error_format json
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Error::Cli(_) => "Cli",
+            Error::Config(_) => "Config",
+            Error::IO(_) => "IO",
+            Error::EmptyProgramInput(_) => "EmptyProgramInput",
+            Error::DuplicateInputKey(_) => "DuplicateInputKey",
+            Error::ProgramInputBase64(_) => "ProgramInputBase64",
+            Error::InputSchema(_) => "InputSchema",
+            Error::MissingBuiltins { .. } => "MissingBuiltins",
+            Error::Hint(_) => "Hint",
+            Error::AmbiguousEntrypoint(_) => "AmbiguousEntrypoint",
+            Error::NoHints => "NoHints",
+            Error::Warnings(_) => "Warnings",
+            Error::UnsupportedHints(_) => "UnsupportedHints",
+            Error::Interrupted => "Interrupted",
+            Error::MemoryLimitExceeded { .. } => "MemoryLimitExceeded",
+            Error::Runner(_) => "Runner",
+            Error::PartialOutput { .. } => "PartialOutput",
+            Error::EncodeTrace(_) => "EncodeTrace",
+            Error::VirtualMachine(_) => "VirtualMachine",
+            Error::Trace(_) => "Trace",
+            Error::PublicInput(_) => "PublicInput",
+            Error::PrivateInput(_) => "PrivateInput",
+            Error::Program(_) => "Program",
+            Error::Msgpack(_) => "Msgpack",
+            Error::Zip(_) => "Zip",
+        }
+    }
+
+    // The error as printed by --error_format json. The message includes the
+    // messages of the underlying errors.
+    pub fn to_json(&self) -> String {
+        let mut message = self.to_string();
+        let mut source = std::error::Error::source(self);
+        while let Some(error) = source {
+            message.push_str(": ");
+            message.push_str(&error.to_string());
+            source = error.source();
+        }
+        serde_json::json!({ "error": message, "kind": self.kind() }).to_string()
+    }
+}
+
+// Opens the output file at `path` for writing. The path `-` stands for the
+// standard output, so that the text outputs (AIR public/private input, output
+// felts) can be piped, and the trace and memory are written there as raw bytes.
+fn create_output_file(path: &Path) -> io::Result<Box<dyn Write>> {
+    if path == Path::new("-") {
+        Ok(Box::new(io::stdout()))
+    } else {
+        Ok(Box::new(std::fs::File::create(path)?))
+    }
+}
+
+// Writes `contents` to the output file at `path` (see `create_output_file`).
+fn write_output_file(path: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> io::Result<()> {
+    let mut file = create_output_file(path.as_ref())?;
+    file.write_all(contents.as_ref())?;
+    file.flush()
+}
+
+// The path the trace or memory file is written to: with --compress_artifacts,
+// the given path with `.gz` appended. The standard output keeps its name.
+fn artifact_path(path: &Path, compress: bool) -> PathBuf {
+    if compress && path != Path::new("-") {
+        let mut path = path.as_os_str().to_owned();
+        path.push(".gz");
+        PathBuf::from(path)
+    } else {
+        path.to_path_buf()
+    }
+}
+
+// A trace or memory file, gzip-compressed with --compress_artifacts
+enum ArtifactWriter {
+    Plain(Box<dyn Write>),
+    Gzip(GzEncoder<Box<dyn Write>>),
+}
+
+impl ArtifactWriter {
+    fn create(path: &Path, compress: bool) -> io::Result<Self> {
+        let file = create_output_file(&artifact_path(path, compress))?;
+        Ok(if compress {
+            ArtifactWriter::Gzip(GzEncoder::new(file, Compression::default()))
+        } else {
+            ArtifactWriter::Plain(file)
+        })
+    }
+
+    // Ends the gzip stream, if any, and flushes the file. Dropping the
+    // encoder would end the stream too, but ignore the errors.
+    fn finish(self) -> io::Result<()> {
+        match self {
+            ArtifactWriter::Plain(mut file) => file.flush(),
+            ArtifactWriter::Gzip(encoder) => encoder.finish()?.flush(),
+        }
+    }
+}
+
+impl Write for ArtifactWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            ArtifactWriter::Plain(file) => file.write(buf),
+            ArtifactWriter::Gzip(encoder) => encoder.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            ArtifactWriter::Plain(file) => file.flush(),
+            ArtifactWriter::Gzip(encoder) => encoder.flush(),
+        }
+    }
+}
+
+// Flushes the buffer and finishes the artifact file.
+fn finish_artifact(buf_writer: io::BufWriter<ArtifactWriter>) -> io::Result<()> {
+    buf_writer
+        .into_inner()
+        .map_err(io::IntoInnerError::into_error)?
+        .finish()
 }
 
 struct FileWriter {
-    buf_writer: io::BufWriter<std::fs::File>,
+    buf_writer: io::BufWriter<ArtifactWriter>,
     bytes_written: usize,
 }
 
@@ -120,25 +578,230 @@ impl Writer for FileWriter {
 }
 
 impl FileWriter {
-    fn new(buf_writer: io::BufWriter<std::fs::File>) -> Self {
+    fn new(buf_writer: io::BufWriter<ArtifactWriter>) -> Self {
         Self {
             buf_writer,
             bytes_written: 0,
         }
     }
 
-    fn flush(&mut self) -> io::Result<()> {
-        self.buf_writer.flush()
+    fn finish(self) -> io::Result<()> {
+        finish_artifact(self.buf_writer)
     }
 }
 
-// The anoma_cairo_vm_runner is used in Anoma to return output, trace, memory,
-// and public input.
-pub fn anoma_cairo_vm_runner(
+// The segment index of the output builtin, if the program uses it
+fn output_base(vm: &VirtualMachine) -> Option<usize> {
+    vm.get_builtin_runners()
+        .iter()
+        .find_map(|builtin| match builtin {
+            BuiltinRunner::Output(output) => Some(output.base()),
+            _ => None,
+        })
+}
+
+// Returns the cells of the output builtin segment up to its used size, with
+// None for the cells the program never wrote, or an empty vector if the
+// program does not use the output builtin.
+fn get_output_cells(vm: &mut VirtualMachine) -> Vec<Option<MaybeRelocatable>> {
+    let Some(base) = output_base(vm) else {
+        return Vec::new();
+    };
+    let size = vm.segments.compute_effective_sizes()[base];
+    (0..size)
+        .map(|i| vm.get_maybe(&Relocatable::from((base as isize, i))))
+        .collect()
+}
+
+// Like get_output_cells, but fails unless every output cell holds a felt.
+fn get_output_felts(vm: &mut VirtualMachine) -> Result<Vec<Felt252>, Error> {
+    let Some(base) = output_base(vm) else {
+        return Ok(Vec::new());
+    };
+    let size = vm.segments.compute_effective_sizes()[base];
+    (0..size)
+        .map(|i| {
+            vm.get_integer(Relocatable::from((base as isize, i)))
+                .map(|felt| felt.into_owned())
+                .map_err(|e| Error::VirtualMachine(VirtualMachineError::Memory(e)))
+        })
+        .collect()
+}
+
+// The offsets of the output cells the program never wrote, below the end of
+// the output segment it returned.
+fn unfilled_output_cells(output_cells: &[Option<MaybeRelocatable>]) -> Vec<usize> {
+    output_cells
+        .iter()
+        .enumerate()
+        .filter_map(|(i, cell)| cell.is_none().then_some(i))
+        .collect()
+}
+
+fn write_msgpack(mut writer: impl Write, value: &impl Serialize) -> Result<(), Error> {
+    rmp_serde::encode::write(&mut writer, value)?;
+    writer.flush()?;
+    Ok(())
+}
+
+// Renders an output cell like VirtualMachine::write_output does, with the
+// felts in the given format.
+fn format_output_cell(cell: &Option<MaybeRelocatable>, format: FeltOutputFormat) -> String {
+    match cell {
+        Some(MaybeRelocatable::Int(felt)) => format_felt(felt, format),
+        Some(MaybeRelocatable::RelocatableValue(addr)) => addr.to_string(),
+        None => "<missing>".to_string(),
+    }
+}
+
+// Renders the output cells one per line, keeping at most `cap` bytes. Also
+// returns whether some of the output was cut off.
+fn format_output(
+    cells: &[Option<MaybeRelocatable>],
+    format: FeltOutputFormat,
+    cap: Option<usize>,
+) -> (String, bool) {
+    let cap = cap.unwrap_or(usize::MAX);
+    let mut output = String::new();
+    for cell in cells {
+        output.push_str(&format_output_cell(cell, format));
+        output.push('\n');
+        if output.len() > cap {
+            // the output is ASCII, so any byte is a char boundary
+            output.truncate(cap);
+            return (output, true);
+        }
+    }
+    (output, false)
+}
+
+fn run_step_error(error: impl Into<CairoRunError>) -> Error {
+    run_error(error.into())
+}
+
+// Runs the program like cairo_run::cairo_run, but if the execution fails,
+// returns the output written before the failure together with the error.
+// `on_failure` is given the runner state at the point of failure.
+fn cairo_run_with_partial_output(
+    program_content: &[u8],
+    cairo_run_config: &cairo_run::CairoRunConfig,
+    hint_executor: &mut JuvixHintProcessor,
+    format: FeltOutputFormat,
+    output_cap: Option<usize>,
+    on_failure: impl FnOnce(&mut CairoRunner, &mut VirtualMachine),
+) -> Result<(CairoRunner, VirtualMachine), Error> {
+    let program = Program::from_bytes(program_content, Some(cairo_run_config.entrypoint))
+        .map_err(run_step_error)?;
+    let secure_run = cairo_run_config
+        .secure_run
+        .unwrap_or(!cairo_run_config.proof_mode);
+    let allow_missing_builtins = cairo_run_config
+        .allow_missing_builtins
+        .unwrap_or(cairo_run_config.proof_mode);
+
+    let mut cairo_runner = CairoRunner::new(
+        &program,
+        cairo_run_config.layout,
+        cairo_run_config.proof_mode,
+    )
+    .map_err(run_step_error)?;
+    let mut vm = VirtualMachine::new(cairo_run_config.trace_enabled);
+    let end = cairo_runner
+        .initialize(&mut vm, allow_missing_builtins)
+        .map_err(run_step_error)?;
+
+    if let Err(err) = cairo_runner.run_until_pc(end, &mut vm, hint_executor) {
+        let error = VmException::from_vm_error(&cairo_runner, &vm, err);
+        on_failure(&mut cairo_runner, &mut vm);
+        let output = format_output(&get_output_cells(&mut vm), format, output_cap).0;
+        return Err(Error::PartialOutput {
+            output,
+            error: Box::new(CairoRunError::VmException(error)),
+        });
+    }
+
+    if cairo_run_config.proof_mode {
+        cairo_runner
+            .run_for_steps(1, &mut vm, hint_executor)
+            .map_err(run_step_error)?;
+    }
+    cairo_runner
+        .end_run(
+            cairo_run_config.disable_trace_padding,
+            false,
+            &mut vm,
+            hint_executor,
+        )
+        .map_err(run_step_error)?;
+    vm.verify_auto_deductions().map_err(run_step_error)?;
+    cairo_runner
+        .read_return_values(&mut vm, allow_missing_builtins)
+        .map_err(run_step_error)?;
+    if cairo_run_config.proof_mode {
+        cairo_runner
+            .finalize_segments(&mut vm)
+            .map_err(run_step_error)?;
+    }
+    if secure_run {
+        verify_secure_runner(&cairo_runner, true, None, &mut vm).map_err(run_step_error)?;
+    }
+    cairo_runner
+        .relocate(&mut vm, cairo_run_config.relocate_mem)
+        .map_err(run_step_error)?;
+
+    Ok((cairo_runner, vm))
+}
+
+// Reports the builtins missing from the layout in a dedicated error, since the
+// runner error does not say how to fix it.
+fn run_error(error: CairoRunError) -> Error {
+    match error {
+        CairoRunError::Runner(RunnerError::NoBuiltinForInstance(data)) => {
+            let (builtins, layout) = *data;
+            let mut builtins: Vec<&str> = builtins.iter().map(|builtin| builtin.to_str()).collect();
+            builtins.sort();
+            Error::MissingBuiltins {
+                builtins: builtins.join(", "),
+                layout: layout.to_string(),
+            }
+        }
+        error => Error::Runner(error),
+    }
+}
+
+// Returns the relocated addresses of the unfilled memory cells. Address 0 is
+// never used by the relocation, so it is not reported.
+fn get_memory_holes(relocated_memory: &[Option<Felt252>]) -> Vec<usize> {
+    relocated_memory
+        .iter()
+        .enumerate()
+        .skip(1)
+        .filter(|(_, cell)| cell.is_none())
+        .map(|(addr, _)| addr)
+        .collect()
+}
+
+// The settings of the anoma runners. The defaults give the artifacts of
+// anoma_cairo_vm_runner.
+#[derive(Debug, Clone, Default)]
+pub struct AnomaRunConfig {
+    // The seed of the RNG RandomEcPoint draws from, so that Anoma controls the
+    // randomness of each run. The artifacts are byte-identical across runs
+    // with the same seed. Without it, the fixed seed is used.
+    pub seed: Option<u64>,
+}
+
+// Runs the program the way Anoma expects: in proof mode with the all_cairo
+// layout, with the trace and memory relocated.
+fn anoma_cairo_vm_run(
     program_content: &[u8],
     program_input: ProgramInput,
-) -> Result<(String, Vec<u8>, Vec<u8>, Vec<u8>), Error> {
+    config: &AnomaRunConfig,
+) -> Result<(CairoRunner, VirtualMachine, String), Error> {
     let mut hint_executor = JuvixHintProcessor::new(program_input);
+    if let Some(seed) = config.seed {
+        hint_executor = hint_executor.with_seed(seed);
+    }
 
     let cairo_run_config = cairo_run::CairoRunConfig {
         trace_enabled: true,
@@ -154,33 +817,70 @@ pub fn anoma_cairo_vm_runner(
     let mut output_buffer = "".to_string();
     vm.write_output(&mut output_buffer)?;
 
-    let trace = {
-        let relocated_trace = cairo_runner
-            .relocated_trace
-            .as_ref()
-            .ok_or(Error::Trace(TraceError::TraceNotRelocated))?;
-        let mut output: Vec<u8> = Vec::with_capacity(3 * 1024 * 1024);
-        for entry in relocated_trace.iter() {
-            output.extend_from_slice(&(entry.ap as u64).to_le_bytes());
-            output.extend_from_slice(&(entry.fp as u64).to_le_bytes());
-            output.extend_from_slice(&(entry.pc as u64).to_le_bytes());
-        }
-        output
-    };
+    Ok((cairo_runner, vm, output_buffer))
+}
 
-    let memory = {
-        let mut output: Vec<u8> = Vec::with_capacity(1024 * 1024);
-        for (i, entry) in cairo_runner.relocated_memory.iter().enumerate() {
-            match entry {
-                None => continue,
-                Some(unwrapped_memory_cell) => {
-                    output.extend_from_slice(&(i as u64).to_le_bytes());
-                    output.extend_from_slice(&unwrapped_memory_cell.to_bytes_le());
-                }
+// Encodes the relocated trace like the trace file in the binary format.
+fn encode_trace(cairo_runner: &CairoRunner) -> Result<Vec<u8>, Error> {
+    let relocated_trace = cairo_runner
+        .relocated_trace
+        .as_ref()
+        .ok_or(Error::Trace(TraceError::TraceNotRelocated))?;
+    let mut output: Vec<u8> = Vec::with_capacity(3 * 1024 * 1024);
+    for entry in relocated_trace.iter() {
+        output.extend_from_slice(&(entry.ap as u64).to_le_bytes());
+        output.extend_from_slice(&(entry.fp as u64).to_le_bytes());
+        output.extend_from_slice(&(entry.pc as u64).to_le_bytes());
+    }
+    Ok(output)
+}
+
+// Encodes the relocated memory like the memory file in the binary format.
+fn encode_memory(relocated_memory: &[Option<Felt252>]) -> Vec<u8> {
+    let mut output: Vec<u8> = Vec::with_capacity(1024 * 1024);
+    for (i, entry) in relocated_memory.iter().enumerate() {
+        match entry {
+            None => continue,
+            Some(unwrapped_memory_cell) => {
+                output.extend_from_slice(&(i as u64).to_le_bytes());
+                output.extend_from_slice(&unwrapped_memory_cell.to_bytes_le());
             }
         }
-        output
-    };
+    }
+    output
+}
+
+// The anoma_cairo_vm_runner is used in Anoma to return output, trace, memory,
+// and public input. Anoma hashes these artifacts, so for a given program and
+// input they are byte-identical across runs and platforms: the memory and
+// trace are encoded in address order, and RandomEcPoint draws from a fixed
+// seed.
+pub fn anoma_cairo_vm_runner(
+    program_content: &[u8],
+    program_input: ProgramInput,
+) -> Result<(String, Vec<u8>, Vec<u8>, Vec<u8>), Error> {
+    let (output, _, trace, memory, public_input) = anoma_cairo_vm_runner_with_felts(
+        program_content,
+        program_input,
+        &AnomaRunConfig::default(),
+    )?;
+    Ok((output, trace, memory, public_input))
+}
+
+// Like anoma_cairo_vm_runner, but with the settings in `config`, and
+// additionally returns the contents of the output segment as felts.
+#[allow(clippy::type_complexity)]
+pub fn anoma_cairo_vm_runner_with_felts(
+    program_content: &[u8],
+    program_input: ProgramInput,
+    config: &AnomaRunConfig,
+) -> Result<(String, Vec<Felt252>, Vec<u8>, Vec<u8>, Vec<u8>), Error> {
+    let (cairo_runner, mut vm, output_buffer) =
+        anoma_cairo_vm_run(program_content, program_input, config)?;
+    let output_felts = get_output_felts(&mut vm)?;
+
+    let trace = encode_trace(&cairo_runner)?;
+    let memory = encode_memory(&cairo_runner.relocated_memory);
 
     let vm_pub_inputs = cairo_runner.get_air_public_input(&vm)?;
 
@@ -196,329 +896,2754 @@ pub fn anoma_cairo_vm_runner(
         output
     };
 
-    Ok((output_buffer, trace, memory, public_input))
+    Ok((output_buffer, output_felts, trace, memory, public_input))
 }
 
-// Returns the program output
-pub fn run(args: Args, program_input: ProgramInput) -> Result<String, Error> {
-    let trace_enabled = args.trace_file.is_some() || args.air_public_input.is_some();
-    let mut hint_executor = JuvixHintProcessor::new(program_input);
-    let cairo_run_config = cairo_run::CairoRunConfig {
-        entrypoint: &args.entrypoint,
-        trace_enabled,
-        relocate_mem: args.memory_file.is_some() || args.air_public_input.is_some(),
-        layout: &args.layout,
-        proof_mode: args.proof_mode,
-        secure_run: args.secure_run,
-        allow_missing_builtins: args.allow_missing_builtins,
-        ..Default::default()
-    };
+// Like anoma_cairo_vm_runner, but with the settings in `config`, and returns
+// the output together with the relocated trace and memory as typed vectors
+// instead of their byte encoding.
+#[allow(clippy::type_complexity)]
+pub fn anoma_cairo_vm_runner_typed(
+    program_content: &[u8],
+    program_input: ProgramInput,
+    config: &AnomaRunConfig,
+) -> Result<(String, Vec<RelocatedTraceEntry>, Vec<Option<Felt252>>), Error> {
+    let (mut cairo_runner, _, output_buffer) =
+        anoma_cairo_vm_run(program_content, program_input, config)?;
+    let trace = cairo_runner
+        .relocated_trace
+        .take()
+        .ok_or(Error::Trace(TraceError::TraceNotRelocated))?;
+    Ok((output_buffer, trace, cairo_runner.relocated_memory))
+}
 
-    let program_content = std::fs::read(args.filename).map_err(Error::IO)?;
+// The result of a (possibly repeated) program run
+#[derive(Debug)]
+pub struct RunResult {
+    // The formatted program output
+    pub output: String,
+    // The wall time of each run
+    pub timings: Vec<Duration>,
+    output_cells: Vec<Option<MaybeRelocatable>>,
+    trace_summary: Option<TraceSummary>,
+    steps: usize,
+    output_truncated: bool,
+    builtin_instance_counts: HashMap<String, usize>,
+    warnings: Vec<String>,
+}
 
-    let (cairo_runner, mut vm) =
-        cairo_run::cairo_run(&program_content, &cairo_run_config, &mut hint_executor)?;
+impl RunResult {
+    // Returns true if the program wrote nothing to the output builtin segment.
+    pub fn output_is_empty(&self) -> bool {
+        self.output_cells.is_empty()
+    }
 
-    let mut output_buffer = "".to_string();
-    vm.write_output(&mut output_buffer)?;
+    // Returns the used size of the output builtin segment, counting the cells
+    // the program left unwritten below the end of its output.
+    pub fn output_len(&self) -> usize {
+        self.output_cells.len()
+    }
 
-    if let Some(ref trace_path) = args.trace_file {
-        let relocated_trace = cairo_runner
-            .relocated_trace
-            .as_ref()
-            .ok_or(Error::Trace(TraceError::TraceNotRelocated))?;
+    // Returns the cells of the output builtin segment, with None for the
+    // unwritten ones.
+    pub fn output_cells(&self) -> &[Option<MaybeRelocatable>] {
+        &self.output_cells
+    }
 
-        let trace_file = std::fs::File::create(trace_path)?;
-        let mut trace_writer =
-            FileWriter::new(io::BufWriter::with_capacity(3 * 1024 * 1024, trace_file));
+    // Returns the output felts, or None if some output cell is unwritten or
+    // holds a pointer.
+    pub fn output_felts(&self) -> Option<Vec<Felt252>> {
+        self.output_cells
+            .iter()
+            .map(|cell| match cell {
+                Some(MaybeRelocatable::Int(felt)) => Some(*felt),
+                _ => None,
+            })
+            .collect()
+    }
 
-        cairo_run::write_encoded_trace(relocated_trace, &mut trace_writer)?;
-        trace_writer.flush()?;
+    // Returns each output cell as in the formatted output: the felts as
+    // signed decimal strings.
+    pub fn output_felt_strings(&self) -> Vec<String> {
+        self.output_cells
+            .iter()
+            .map(|cell| format_output_cell(cell, FeltOutputFormat::Dec))
+            .collect()
     }
 
-    if let Some(ref memory_path) = args.memory_file {
-        let memory_file = std::fs::File::create(memory_path)?;
-        let mut memory_writer =
-            FileWriter::new(io::BufWriter::with_capacity(5 * 1024 * 1024, memory_file));
+    // Returns the number of instances of each builtin the program used, by
+    // builtin name.
+    pub fn builtin_instance_counts(&self) -> HashMap<String, usize> {
+        self.builtin_instance_counts.clone()
+    }
 
-        cairo_run::write_encoded_memory(&cairo_runner.relocated_memory, &mut memory_writer)?;
-        memory_writer.flush()?;
+    // The builtin instance counts ordered by builtin name, as printed by
+    // --print_builtin_counts
+    pub fn format_builtin_counts(&self) -> String {
+        self.builtin_instance_counts
+            .iter()
+            .collect::<BTreeMap<_, _>>()
+            .into_iter()
+            .map(|(name, count)| format!("{name}: {count}\n"))
+            .collect()
     }
 
-    if let Some(file_path) = args.air_public_input {
-        let json = cairo_runner.get_air_public_input(&vm)?.serialize_json()?;
-        std::fs::write(file_path, json)?;
+    // Returns the warnings issued during the run, which were also printed.
+    pub fn warnings(&self) -> &[String] {
+        &self.warnings
     }
 
-    if let (Some(file_path), Some(ref trace_file), Some(ref memory_file)) =
-        (args.air_private_input, args.trace_file, args.memory_file)
-    {
-        // Get absolute paths of trace_file & memory_file
-        let trace_path = trace_file
-            .as_path()
-            .canonicalize()
-            .unwrap_or(trace_file.clone())
-            .to_string_lossy()
-            .to_string();
-        let memory_path = memory_file
-            .as_path()
-            .canonicalize()
-            .unwrap_or(memory_file.clone())
-            .to_string_lossy()
-            .to_string();
+    // Returns true if the output was cut off at --output_cap bytes.
+    pub fn output_truncated(&self) -> bool {
+        self.output_truncated
+    }
 
-        let json = cairo_runner
-            .get_air_private_input(&vm)
-            .to_serializable(trace_path, memory_path)
-            .serialize_json()
-            .map_err(PublicInputError::Serde)?;
-        std::fs::write(file_path, json)?;
+    // Returns the number of steps of the last run.
+    pub fn steps(&self) -> usize {
+        self.steps
     }
 
-    if let Some(ref file_name) = args.cairo_pie_output {
-        let file_path = Path::new(file_name);
-        cairo_runner
-            .get_cairo_pie(&vm)
-            .map_err(CairoRunError::Runner)?
-            .write_zip_file(file_path)?
+    // Returns the trace summary if one was requested with --print_trace_summary.
+    pub fn trace_summary(&self) -> Option<&TraceSummary> {
+        self.trace_summary.as_ref()
     }
+}
 
-    Ok(output_buffer)
+// An overview of the relocated trace, as reported by --print_trace_summary
+#[derive(Debug, PartialEq, Eq)]
+pub struct TraceSummary {
+    pub len: usize,
+    // The (pc, ap, fp) registers of the first and last steps
+    pub first: (usize, usize, usize),
+    pub last: (usize, usize, usize),
+    // The lowest and highest pc executed
+    pub pc_range: (usize, usize),
 }
 
-pub fn run_cli(args: impl Iterator<Item = String>) -> Result<(), Error> {
-    let args = Args::try_parse_from(args)?;
-    let program_input;
-    if let Some(ref file) = args.program_input {
-        program_input = ProgramInput::from_json(std::fs::read_to_string(file)?.as_str())?;
-    } else {
-        program_input = ProgramInput::new(HashMap::new());
+impl TraceSummary {
+    fn from_trace(trace: &[RelocatedTraceEntry]) -> Option<Self> {
+        let registers = |entry: &RelocatedTraceEntry| (entry.pc, entry.ap, entry.fp);
+        Some(TraceSummary {
+            len: trace.len(),
+            first: registers(trace.first()?),
+            last: registers(trace.last()?),
+            pc_range: (
+                trace.iter().map(|entry| entry.pc).min()?,
+                trace.iter().map(|entry| entry.pc).max()?,
+            ),
+        })
     }
-    let print_output = args.print_output;
-    match run(args, program_input) {
-        Ok(output) => {
-            if print_output {
-                print!("{output}");
-            }
-            Ok(())
-        }
-        Err(Error::Runner(error)) => {
-            eprintln!("{error}");
-            Err(Error::Runner(error))
-        }
-        Err(err) => Err(err),
+}
+
+impl fmt::Display for TraceSummary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "trace length: {}", self.len)?;
+        let (pc, ap, fp) = self.first;
+        writeln!(f, "first step: pc {pc}, ap {ap}, fp {fp}")?;
+        let (pc, ap, fp) = self.last;
+        writeln!(f, "last step: pc {pc}, ap {ap}, fp {fp}")?;
+        writeln!(f, "pc range: {}..={}", self.pc_range.0, self.pc_range.1)
     }
 }
 
-#[cfg(test)]
-mod tests {
-    #![allow(clippy::too_many_arguments)]
-    use super::*;
-    use assert_matches::assert_matches;
-    use rstest::rstest;
+// What a compiled program declares, as reported by --program_info
+#[derive(Debug, PartialEq, Eq)]
+pub struct ProgramInfo {
+    pub builtins: Vec<String>,
+    // The functions of the main scope with their offsets, ordered by offset
+    pub entrypoints: Vec<(String, usize)>,
+    pub data_len: usize,
+}
 
-    #[rstest]
-    #[case([].as_slice())]
-    #[case(["juvix-cairo-vm"].as_slice())]
-    fn test_run_missing_mandatory_args(#[case] args: &[&str]) {
-        let args = args.iter().cloned().map(String::from);
-        assert_matches!(run_cli(args), Err(Error::Cli(_)));
+impl fmt::Display for ProgramInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "builtins: [{}]", self.builtins.join(", "))?;
+        writeln!(f, "entrypoints:")?;
+        for (name, pc) in self.entrypoints.iter() {
+            writeln!(f, "  {name}: {pc}")?;
+        }
+        writeln!(f, "data length: {}", self.data_len)
     }
+}
 
-    #[rstest]
-    #[case(["juvix-cairo-vm", "--layout", "broken_layout", "../tests/fibonacci.json"].as_slice())]
-    fn test_run_invalid_args(#[case] args: &[&str]) {
-        let args = args.iter().cloned().map(String::from);
-        assert_matches!(run_cli(args), Err(Error::Cli(_)));
-    }
+// The functions of the main scope with their offsets, ordered by offset. Any
+// of them can be passed as --entrypoint.
+fn entrypoints_of(program_json: &ProgramJson) -> Vec<(String, usize)> {
+    let mut entrypoints: Vec<(String, usize)> = program_json
+        .identifiers
+        .iter()
+        .filter(|(_, identifier)| identifier.type_.as_deref() == Some("function"))
+        .filter_map(|(name, identifier)| {
+            let name = name.strip_prefix("__main__.")?;
+            Some((name.to_string(), identifier.pc?))
+        })
+        .collect();
+    entrypoints.sort_by_key(|(name, pc)| (*pc, name.clone()));
+    entrypoints
+}
 
-    #[rstest]
-    #[case(["juvix-cairo-vm", "tests/fibonacci.json", "--air_private_input", "/dev/null", "--proof_mode", "--memory_file", "/dev/null"].as_slice())]
-    fn test_run_air_private_input_no_trace(#[case] args: &[&str]) {
-        let args = args.iter().cloned().map(String::from);
-        assert_matches!(run_cli(args), Err(Error::Cli(_)));
+// The entrypoint to run. When the default `main` is requested but the program
+// has no such function, its only entrypoint is used instead.
+fn resolve_entrypoint(program_content: &[u8], entrypoint: &str) -> Result<String, Error> {
+    if entrypoint != "main" {
+        return Ok(entrypoint.to_string());
     }
-
-    #[rstest]
-    #[case(["juvix-cairo-vm", "tests/fibonacci.json", "--air_private_input", "/dev/null", "--proof_mode", "--trace_file", "/dev/null"].as_slice())]
-    fn test_run_air_private_input_no_memory(#[case] args: &[&str]) {
-        let args = args.iter().cloned().map(String::from);
-        assert_matches!(run_cli(args), Err(Error::Cli(_)));
+    // an unreadable program is reported by the runner
+    let Ok(program_json) = deserialize_program_json(program_content) else {
+        return Ok(entrypoint.to_string());
+    };
+    let mut names: Vec<String> = entrypoints_of(&program_json)
+        .into_iter()
+        .map(|(name, _)| name)
+        .collect();
+    if names.is_empty() || names.iter().any(|name| name == "main") {
+        return Ok(entrypoint.to_string());
     }
-
-    #[rstest]
-    #[case(["juvix-cairo-vm", "tests/fibonacci.json", "--air_private_input", "/dev/null", "--trace_file", "/dev/null", "--memory_file", "/dev/null"].as_slice())]
-    fn test_run_air_private_input_no_proof(#[case] args: &[&str]) {
-        let args = args.iter().cloned().map(String::from);
-        assert_matches!(run_cli(args), Err(Error::Cli(_)));
+    if names.len() > 1 {
+        return Err(Error::AmbiguousEntrypoint(names));
     }
+    let name = names.remove(0);
+    warning::warn(format!(
+        "the program has no main function, running its only entrypoint {name}"
+    ));
+    Ok(name)
+}
 
-    #[rstest]
-    fn test_run_ok(
-        #[values(None,
-                 Some("plain"),
-                 Some("small"),
-                 Some("dex"),
-                 Some("starknet"),
-                 Some("starknet_with_keccak"),
-                 Some("recursive_large_output"),
-                 Some("all_cairo"),
-                 Some("all_solidity"),
-                 //FIXME: dynamic layout leads to _very_ slow execution
-                 //Some("dynamic"),
-        )]
-        layout: Option<&str>,
-        #[values(false, true)] memory_file: bool,
-        #[values(false, true)] mut trace_file: bool,
-        #[values(false, true)] proof_mode: bool,
-        #[values(false, true)] print_output: bool,
-        #[values(false, true)] entrypoint: bool,
-        #[values(false, true)] air_public_input: bool,
-        #[values(false, true)] air_private_input: bool,
-        #[values(false, true)] cairo_pie_output: bool,
-    ) {
-        let mut args = vec!["juvix-cairo-vm".to_string()];
-        if let Some(layout) = layout {
-            args.extend_from_slice(&["--layout".to_string(), layout.to_string()]);
+// Lists the functions of the compiled program that can serve as entrypoints.
+pub fn list_entrypoints(program_content: &[u8]) -> Result<Vec<(String, usize)>, Error> {
+    Ok(entrypoints_of(&deserialize_program_json(program_content)?))
+}
+
+// Parses the compiled program without running it.
+pub fn program_info(program_content: &[u8]) -> Result<ProgramInfo, Error> {
+    let program_json = deserialize_program_json(program_content)?;
+    let entrypoints = entrypoints_of(&program_json);
+    Ok(ProgramInfo {
+        builtins: program_json
+            .builtins
+            .iter()
+            .map(|builtin| builtin.to_str().to_string())
+            .collect(),
+        entrypoints,
+        data_len: program_json.data.len(),
+    })
+}
+
+// Sorts the keys of the JSON objects, recursively, so that equal programs
+// serialize the same way whatever the key order of their files.
+fn canonical_json(value: serde_json::Value) -> serde_json::Value {
+    match value {
+        serde_json::Value::Object(map) => {
+            let mut entries: Vec<_> = map.into_iter().collect();
+            entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+            serde_json::Value::Object(
+                entries
+                    .into_iter()
+                    .map(|(key, value)| (key, canonical_json(value)))
+                    .collect(),
+            )
         }
-        if air_public_input {
-            args.extend_from_slice(&["--air_public_input".to_string(), "/dev/null".to_string()]);
+        serde_json::Value::Array(values) => {
+            serde_json::Value::Array(values.into_iter().map(canonical_json).collect())
         }
-        if air_private_input {
-            args.extend_from_slice(&["--air_private_input".to_string(), "/dev/null".to_string()]);
+        value => value,
+    }
+}
+
+// The Keccak-256 digest, in hexadecimal, of the compiled program JSON with its
+// object keys sorted and without whitespace. It does not depend on the key
+// order or formatting of the file.
+pub fn program_hash(program_content: &[u8]) -> Result<String, Error> {
+    let program: serde_json::Value = serde_json::from_slice(program_content)?;
+    let canonical = serde_json::to_vec(&canonical_json(program))?;
+    Ok(keccak_hex(&canonical))
+}
+
+// The Keccak-256 digest of `bytes` in hexadecimal
+fn keccak_hex(bytes: &[u8]) -> String {
+    Keccak256::digest(bytes)
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+// What a run produced, as written by --manifest for a downstream prover
+#[derive(Debug, Serialize)]
+struct Manifest<'a> {
+    program: &'a Path,
+    program_hash: String,
+    entrypoint: &'a str,
+    layout: &'a str,
+    proof_mode: bool,
+    steps: usize,
+    output_len: usize,
+    artifacts: Vec<ManifestArtifact>,
+}
+
+#[derive(Debug, Serialize)]
+struct ManifestArtifact {
+    kind: &'static str,
+    path: PathBuf,
+    size: u64,
+    // Only with --manifest_hashes, as hashing a large trace takes a while
+    #[serde(skip_serializing_if = "Option::is_none")]
+    keccak256: Option<String>,
+}
+
+// Describes the artifacts written by the run. Those written to the standard
+// output are left out.
+fn manifest_artifacts(args: &Args) -> io::Result<Vec<ManifestArtifact>> {
+    let files = [
+        (
+            "trace",
+            args.trace_file
+                .as_ref()
+                .map(|path| artifact_path(path, args.compress_artifacts)),
+        ),
+        (
+            "memory",
+            args.memory_file
+                .as_ref()
+                .map(|path| artifact_path(path, args.compress_artifacts)),
+        ),
+        ("memory_holes", args.memory_holes_json.clone()),
+        ("output_felts", args.emit_output_felts.clone()),
+        (
+            "air_public_input",
+            args.air_public_input.as_ref().map(PathBuf::from),
+        ),
+        (
+            "air_private_input",
+            args.air_private_input.as_ref().map(PathBuf::from),
+        ),
+        (
+            "cairo_pie",
+            args.cairo_pie_output.as_ref().map(PathBuf::from),
+        ),
+    ];
+    files
+        .into_iter()
+        .filter_map(|(kind, path)| Some((kind, path?)))
+        .filter(|(_, path)| path != Path::new("-"))
+        .map(|(kind, path)| {
+            let size = std::fs::metadata(&path)?.len();
+            let keccak256 = if args.manifest_hashes {
+                Some(keccak_hex(&std::fs::read(&path)?))
+            } else {
+                None
+            };
+            Ok(ManifestArtifact {
+                kind,
+                path,
+                size,
+                keccak256,
+            })
+        })
+        .collect()
+}
+
+// Parses every hint of the compiled program, in the order of their pc.
+pub fn collect_hints(program_content: &[u8]) -> Result<Vec<Hint>, Error> {
+    let program_json = deserialize_program_json(program_content)?;
+    program_json
+        .hints
+        .values()
+        .flatten()
+        .map(|hint| {
+            hint.code
+                .parse::<Hint>()
+                .map_err(|e| Error::Hint(e.message))
+        })
+        .collect()
+}
+
+// Returns the hints of the compiled program which fail to parse, as pairs of
+// their pc and code, in the order of their pc.
+pub fn unsupported_hints(program_content: &[u8]) -> Result<Vec<(usize, String)>, Error> {
+    let program_json = deserialize_program_json(program_content)?;
+    Ok(program_json
+        .hints
+        .iter()
+        .flat_map(|(pc, hints)| hints.iter().map(move |hint| (*pc, &hint.code)))
+        .filter(|(_, code)| code.parse::<Hint>().is_err())
+        .map(|(pc, code)| (pc, code.clone()))
+        .collect())
+}
+
+// The number of hints of each kind, ordered by kind, as printed by --list_hints
+fn format_hint_census(hints: &[Hint]) -> String {
+    let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+    for hint in hints {
+        *counts.entry(hint.name()).or_default() += 1;
+    }
+    counts
+        .iter()
+        .map(|(name, count)| format!("{name}: {count}\n"))
+        .collect()
+}
+
+// Returns the program output
+pub fn run(args: Args, program_input: ProgramInput) -> Result<String, Error> {
+    run_timed(args, program_input).map(|result| result.output)
+}
+
+// Runs the program `args.repeat` times, resetting the hint processor each time,
+// and returns the output of the last run together with the wall time of each
+// run. The artifacts are written only for the last run.
+pub fn run_timed(args: Args, program_input: ProgramInput) -> Result<RunResult, Error> {
+    let program_content = std::fs::read(&args.filename).map_err(Error::IO)?;
+    run_program(&program_content, &args, &program_input)
+}
+
+// Like run_timed, but with the program contents given directly, and without
+// taking the arguments or the input, so that they can be used for another
+// run. The program file named in `args` is not read.
+pub fn run_borrowed(
+    program_content: &[u8],
+    args: &Args,
+    program_input: &ProgramInput,
+) -> Result<RunResult, Error> {
+    run_program(program_content, args, program_input)
+}
+
+// Runs a compiled program given as a JSON string with the input given as a
+// string, which is read like a --program_input file: in the configured input
+// format and validated against the configured schema. The settings not given
+// in `config` take their CLI defaults.
+pub fn run_json(program_json: &str, input: &str, config: Config) -> Result<RunResult, Error> {
+    let args = config::args_from_config(config)?;
+    let program_input = parse_program_input(&args, input, args.input_format)?;
+    run_program(program_json.as_bytes(), &args, &program_input)
+}
+
+// What a prover needs from a proof mode run
+#[derive(Debug)]
+pub struct ProofInputs {
+    // The AIR public input JSON
+    pub public_input: String,
+    // The AIR private input JSON. Its trace and memory paths are empty since
+    // the trace and memory are returned alongside instead of written to files.
+    pub private_input: String,
+    // The trace and memory in the binary format of the trace and memory files
+    pub trace: Vec<u8>,
+    pub memory: Vec<u8>,
+}
+
+// Runs the program in proof mode and returns the AIR inputs with the encoded
+// trace and memory, without writing any file. The settings not given in
+// `config` take their CLI defaults; the proof mode setting is ignored.
+pub fn run_proof(
+    program_content: &[u8],
+    program_input: ProgramInput,
+    config: Config,
+) -> Result<ProofInputs, Error> {
+    let args = config::args_from_config(config)?;
+    let entrypoint = resolve_entrypoint(program_content, &args.entrypoint)?;
+    let cairo_run_config = cairo_run::CairoRunConfig {
+        entrypoint: &entrypoint,
+        trace_enabled: true,
+        relocate_mem: true,
+        layout: &args.layout,
+        proof_mode: true,
+        secure_run: args.secure_run,
+        allow_missing_builtins: args.allow_missing_builtins,
+        ..Default::default()
+    };
+    let mut hint_executor = JuvixHintProcessor::new(program_input);
+    if args.builtin_hints {
+        hint_executor = hint_executor.with_builtin_hints();
+    }
+    if args.strict_alloc {
+        hint_executor = hint_executor.with_strict_alloc();
+    }
+    if let Some(seed) = args.seed {
+        hint_executor = hint_executor.with_seed(seed);
+    }
+    let (cairo_runner, vm) =
+        cairo_run::cairo_run(program_content, &cairo_run_config, &mut hint_executor)
+            .map_err(run_error)?;
+
+    let public_input = cairo_runner.get_air_public_input(&vm)?.serialize_json()?;
+    let private_input = cairo_runner
+        .get_air_private_input(&vm)
+        .to_serializable(String::new(), String::new())
+        .serialize_json()
+        .map_err(PublicInputError::Serde)?;
+    Ok(ProofInputs {
+        public_input,
+        private_input,
+        trace: encode_trace(&cairo_runner)?,
+        memory: encode_memory(&cairo_runner.relocated_memory),
+    })
+}
+
+// The path of the trace or memory file the AIR private input refers to when
+// the user named none: next to the private input, with `.trace` or `.memory`
+// appended, since the prover reads it alongside.
+fn private_input_artifact_path(private_input: &str, kind: &str) -> PathBuf {
+    PathBuf::from(format!("{private_input}.{kind}"))
+}
+
+// Like run_timed, but with the program contents given directly. The program
+// file named in `args` is not read.
+fn run_program(
+    program_content: &[u8],
+    args: &Args,
+    program_input: &ProgramInput,
+) -> Result<RunResult, Error> {
+    if args.require_hints {
+        let program_json = deserialize_program_json(program_content)?;
+        let mut hints = program_json.hints.values().flatten();
+        if !hints.any(|hint| hint.code.parse::<Hint>().is_ok()) {
+            return Err(Error::NoHints);
         }
-        if cairo_pie_output {
-            args.extend_from_slice(&["--cairo_pie_output".to_string(), "/dev/null".to_string()]);
+    }
+
+    // forget the warnings of earlier runs on this thread
+    warning::take();
+    // the arguments are only copied when the AIR private input needs trace
+    // and memory files the user did not name
+    let args = match args {
+        Args {
+            air_private_input: Some(private_input),
+            trace_file,
+            memory_file,
+            ..
+        } if trace_file.is_none() || memory_file.is_none() => Cow::Owned(Args {
+            trace_file: trace_file
+                .clone()
+                .or_else(|| Some(private_input_artifact_path(private_input, "trace"))),
+            memory_file: memory_file
+                .clone()
+                .or_else(|| Some(private_input_artifact_path(private_input, "memory"))),
+            ..args.clone()
+        }),
+        args => Cow::Borrowed(args),
+    };
+    let entrypoint = resolve_entrypoint(program_content, &args.entrypoint)?;
+
+    let trace_enabled =
+        args.trace_file.is_some() || args.air_public_input.is_some() || args.print_trace_summary;
+    let cairo_run_config = cairo_run::CairoRunConfig {
+        entrypoint: &entrypoint,
+        trace_enabled,
+        relocate_mem: args.memory_file.is_some()
+            || args.air_public_input.is_some()
+            || args.memory_holes_json.is_some()
+            || args.max_memory_cells.is_some()
+            || args.trace_include_registers,
+        layout: &args.layout,
+        proof_mode: args.proof_mode,
+        secure_run: args.secure_run,
+        // all_cairo has every builtin the prover supports, so a builtin missing
+        // from it is one the program cannot use anyway
+        allow_missing_builtins: args
+            .allow_missing_builtins
+            .or((args.layout == "all_cairo").then_some(true)),
+        ..Default::default()
+    };
+
+    let mut hint_executor = JuvixHintProcessor::borrowed(program_input);
+    if args.builtin_hints {
+        hint_executor = hint_executor.with_builtin_hints();
+    }
+    if args.strict_alloc {
+        hint_executor = hint_executor.with_strict_alloc();
+    }
+    if let Some(seed) = args.seed {
+        hint_executor = hint_executor.with_seed(seed);
+    }
+    if args.handle_interrupt {
+        interrupt::install_handler();
+        hint_executor = hint_executor.with_interrupt(&interrupt::INTERRUPTED);
+    }
+    if args.progress {
+        hint_executor = hint_executor.with_progress();
+    }
+
+    let mut timings = Vec::with_capacity(args.repeat);
+    let (cairo_runner, mut vm) = loop {
+        hint_executor.reset();
+        let start = Instant::now();
+        let result = if args.no_output_on_error && !args.best_effort {
+            cairo_run::cairo_run(program_content, &cairo_run_config, &mut hint_executor)
+                .map_err(run_error)
+        } else {
+            cairo_run_with_partial_output(
+                program_content,
+                &cairo_run_config,
+                &mut hint_executor,
+                args.felt_output_format,
+                args.output_cap,
+                |cairo_runner, vm| {
+                    if args.best_effort {
+                        write_partial_artifacts(cairo_runner, vm, &args)
+                    }
+                },
+            )
+            .map_err(|err| match err {
+                Error::PartialOutput { error, .. } if args.no_output_on_error => {
+                    Error::Runner(*error)
+                }
+                err => err,
+            })
+        };
+        if hint_executor.interrupted() {
+            return Err(Error::Interrupted);
         }
-        if proof_mode {
-            trace_file = true;
-            args.extend_from_slice(&["--proof_mode".to_string()]);
+        let result = result?;
+        timings.push(start.elapsed());
+        if timings.len() >= args.repeat {
+            break result;
         }
-        if entrypoint {
-            args.extend_from_slice(&["--entrypoint".to_string(), "main".to_string()]);
+    };
+
+    if let Some(limit) = args.max_memory_cells {
+        let used = cairo_runner
+            .relocated_memory
+            .iter()
+            .filter(|cell| cell.is_some())
+            .count();
+        if used > limit {
+            return Err(Error::MemoryLimitExceeded { used, limit });
         }
-        if memory_file {
-            args.extend_from_slice(&["--memory_file".to_string(), "/dev/null".to_string()]);
+    }
+
+    let output_cells = get_output_cells(&mut vm);
+    // the segment sizes were computed by get_output_cells
+    let builtin_instance_counts = vm
+        .get_builtin_runners()
+        .iter()
+        .map(|builtin| {
+            let count = builtin.get_used_instances(&vm.segments)?;
+            Ok((builtin.name().to_str().to_string(), count))
+        })
+        .collect::<Result<HashMap<_, _>, _>>()
+        .map_err(|e| Error::VirtualMachine(VirtualMachineError::Memory(e)))?;
+
+    if args.warn_unfilled_output {
+        let unfilled = unfilled_output_cells(&output_cells);
+        if !unfilled.is_empty() {
+            let offsets: Vec<String> = unfilled.iter().map(usize::to_string).collect();
+            warning::warn(format!(
+                "the program did not fill its output: the output cells at offsets {} were \
+                 never written",
+                offsets.join(", ")
+            ));
         }
-        if trace_file {
-            args.extend_from_slice(&["--trace_file".to_string(), "/dev/null".to_string()]);
+    }
+
+    let steps = cairo_runner
+        .get_execution_resources(&vm)
+        .map_err(CairoRunError::Runner)?
+        .n_steps;
+
+    let (output_buffer, output_truncated) =
+        format_output(&output_cells, args.felt_output_format, args.output_cap);
+    if output_truncated {
+        warning::warn(format!(
+            "the output was truncated to {} bytes",
+            output_buffer.len()
+        ));
+    }
+
+    if let Some(ref file_path) = args.emit_output_felts {
+        // the unwritten cells are null rather than the `<missing>` marker of
+        // the printed output, which could be taken for a felt
+        let felts: Vec<Option<String>> = output_cells
+            .iter()
+            .map(|cell| {
+                cell.as_ref()
+                    .map(|_| format_output_cell(cell, args.felt_output_format))
+            })
+            .collect();
+        write_output_file(file_path, serde_json::to_string(&felts)?)?;
+    }
+
+    let trace_summary = if args.print_trace_summary {
+        let relocated_trace = cairo_runner
+            .relocated_trace
+            .as_ref()
+            .ok_or(Error::Trace(TraceError::TraceNotRelocated))?;
+        TraceSummary::from_trace(relocated_trace)
+    } else {
+        None
+    };
+
+    if let Some(ref trace_path) = args.trace_file {
+        write_trace(&cairo_runner, trace_path, &args)?;
+    }
+
+    if let Some(ref memory_path) = args.memory_file {
+        write_memory(&cairo_runner, memory_path, &args)?;
+    }
+
+    if let Some(ref file_path) = args.memory_holes_json {
+        let holes = get_memory_holes(&cairo_runner.relocated_memory);
+        write_output_file(file_path, serde_json::to_string(&holes)?)?;
+    }
+
+    if let Some(ref file_path) = args.air_public_input {
+        let json = cairo_runner.get_air_public_input(&vm)?.serialize_json()?;
+        write_output_file(file_path, json)?;
+    }
+
+    if let (Some(ref file_path), Some(ref trace_file), Some(ref memory_file)) =
+        (&args.air_private_input, &args.trace_file, &args.memory_file)
+    {
+        // Get absolute paths of trace_file & memory_file
+        let absolute_path = |path: PathBuf| {
+            path.canonicalize()
+                .unwrap_or_else(|_| {
+                    warning::warn(format!(
+                        "could not resolve the absolute path of {}, the AIR private input \
+                         refers to it as given",
+                        path.display()
+                    ));
+                    path
+                })
+                .to_string_lossy()
+                .to_string()
+        };
+        let trace_path = absolute_path(artifact_path(trace_file, args.compress_artifacts));
+        let memory_path = absolute_path(artifact_path(memory_file, args.compress_artifacts));
+
+        let json = cairo_runner
+            .get_air_private_input(&vm)
+            .to_serializable(trace_path, memory_path)
+            .serialize_json()
+            .map_err(PublicInputError::Serde)?;
+        write_output_file(file_path, json)?;
+    }
+
+    if let Some(ref file_name) = args.cairo_pie_output {
+        let file_path = Path::new(file_name);
+        let cairo_pie = cairo_runner
+            .get_cairo_pie(&vm)
+            .map_err(CairoRunError::Runner)?;
+        write_cairo_pie(&cairo_pie, file_path, args.cairo_pie_compression)?
+    }
+
+    if let Some(ref file_path) = args.manifest {
+        let manifest = Manifest {
+            program: &args.filename,
+            program_hash: program_hash(program_content)?,
+            entrypoint: &entrypoint,
+            layout: &args.layout,
+            proof_mode: args.proof_mode,
+            steps,
+            output_len: output_cells.len(),
+            artifacts: manifest_artifacts(&args)?,
+        };
+        write_output_file(file_path, serde_json::to_string_pretty(&manifest)?)?;
+    }
+
+    let warnings = warning::take();
+    if args.fail_on_warnings && !warnings.is_empty() {
+        return Err(Error::Warnings(warnings));
+    }
+
+    Ok(RunResult {
+        output: output_buffer,
+        timings,
+        output_cells,
+        trace_summary,
+        steps,
+        output_truncated,
+        builtin_instance_counts,
+        warnings,
+    })
+}
+
+// The cost of running a program under one layout, as reported by
+// --benchmark_layouts
+#[derive(Debug)]
+pub struct LayoutBenchmark {
+    pub layout: &'static str,
+    pub steps: usize,
+    // The mean wall time of the `args.repeat` runs
+    pub time: Duration,
+}
+
+// Returns true if `layout` provides every builtin the program uses. This is
+// decided by initializing the builtins of a runner for the layout, as the
+// layout definitions are private to the VM.
+fn layout_has_builtins(program: &Program, layout: &str, proof_mode: bool) -> Result<bool, Error> {
+    let cairo_runner = CairoRunner::new(program, layout, proof_mode).map_err(run_step_error)?;
+    let mut vm = VirtualMachine::new(false);
+    match cairo_runner.initialize_builtins(&mut vm, false) {
+        Ok(()) => Ok(true),
+        Err(RunnerError::NoBuiltinForInstance(_)) => Ok(false),
+        Err(err) => Err(run_step_error(err)),
+    }
+}
+
+// Runs the program under every layout which has the builtins it needs, with
+// the trace and all the artifacts disabled. The layouts missing a builtin are
+// left out, whether missing builtins are allowed or not.
+pub fn benchmark_layouts(
+    program_content: &[u8],
+    args: &Args,
+    program_input: ProgramInput,
+) -> Result<Vec<LayoutBenchmark>, Error> {
+    let program = Program::from_bytes(program_content, None).map_err(run_step_error)?;
+    let mut benchmarks = Vec::new();
+    for layout in LAYOUTS {
+        if !layout_has_builtins(&program, layout, args.proof_mode)? {
+            continue;
         }
-        if print_output {
-            args.extend_from_slice(&["--print_output".to_string()]);
+        let args = Args {
+            layout: layout.to_string(),
+            trace_file: None,
+            memory_file: None,
+            memory_holes_json: None,
+            emit_output_felts: None,
+            air_public_input: None,
+            air_private_input: None,
+            cairo_pie_output: None,
+            manifest: None,
+            print_trace_summary: false,
+            max_memory_cells: None,
+            ..args.clone()
+        };
+        let result = run_program(program_content, &args, &program_input)?;
+        benchmarks.push(LayoutBenchmark {
+            layout,
+            steps: result.steps,
+            time: result.timings.iter().sum::<Duration>() / result.timings.len() as u32,
+        });
+    }
+    Ok(benchmarks)
+}
+
+// The table printed by --benchmark_layouts, one row per layout
+fn format_layout_benchmarks(benchmarks: &[LayoutBenchmark]) -> String {
+    let mut table = format!("{:<24}{:>12}{:>16}\n", "layout", "steps", "time");
+    for benchmark in benchmarks {
+        table.push_str(&format!(
+            "{:<24}{:>12}{:>16}\n",
+            benchmark.layout,
+            benchmark.steps,
+            format!("{:?}", benchmark.time)
+        ));
+    }
+    table
+}
+
+fn write_trace(cairo_runner: &CairoRunner, trace_path: &Path, args: &Args) -> Result<(), Error> {
+    let relocated_trace = cairo_runner
+        .relocated_trace
+        .as_ref()
+        .ok_or(Error::Trace(TraceError::TraceNotRelocated))?;
+
+    let trace_file = ArtifactWriter::create(trace_path, args.compress_artifacts)?;
+    let mut buf_writer = io::BufWriter::with_capacity(args.trace_buffer_size, trace_file);
+
+    match args.trace_format {
+        ArtifactFormat::Binary => {
+            let mut trace_writer = FileWriter::new(buf_writer);
+            cairo_run::write_encoded_trace(relocated_trace, &mut trace_writer)?;
+            trace_writer.finish()?;
+        }
+        ArtifactFormat::Msgpack => {
+            write_msgpack(&mut buf_writer, relocated_trace)?;
+            finish_artifact(buf_writer)?;
         }
+        ArtifactFormat::Json => {
+            let entries: Vec<JsonTraceEntry> = relocated_trace
+                .iter()
+                .map(|entry| JsonTraceEntry {
+                    pc: entry.pc,
+                    ap: entry.ap,
+                    fp: entry.fp,
+                    opcode: args
+                        .trace_include_registers
+                        .then(|| opcode_mnemonic(&cairo_runner.relocated_memory, entry.pc)),
+                })
+                .collect();
+            serde_json::to_writer(&mut buf_writer, &entries)?;
+            finish_artifact(buf_writer)?;
+        }
+    }
+    Ok(())
+}
+
+// A step of the JSON trace
+#[derive(Serialize)]
+struct JsonTraceEntry {
+    pc: usize,
+    ap: usize,
+    fp: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    opcode: Option<&'static str>,
+}
+
+// The mnemonic of the instruction at the relocated address `pc`: `call`,
+// `ret`, `assert_eq`, `jmp`, `jnz`, `ap_add` (for `ap += n`) or `nop`, or
+// `unknown` if the cell does not hold an instruction.
+fn opcode_mnemonic(relocated_memory: &[Option<Felt252>], pc: usize) -> &'static str {
+    let instruction = relocated_memory
+        .get(pc)
+        .copied()
+        .flatten()
+        .and_then(|felt| felt_to_usize(&felt).ok())
+        .and_then(|encoded| decode_instruction(encoded as u64).ok());
+    let Some(instruction) = instruction else {
+        return "unknown";
+    };
+    match (instruction.opcode, instruction.pc_update) {
+        (Opcode::Call, _) => "call",
+        (Opcode::Ret, _) => "ret",
+        (Opcode::AssertEq, _) => "assert_eq",
+        (Opcode::NOp, PcUpdate::Jump | PcUpdate::JumpRel) => "jmp",
+        (Opcode::NOp, PcUpdate::Jnz) => "jnz",
+        (Opcode::NOp, PcUpdate::Regular) if instruction.ap_update == ApUpdate::Add => "ap_add",
+        (Opcode::NOp, PcUpdate::Regular) => "nop",
+    }
+}
+
+fn write_memory(cairo_runner: &CairoRunner, memory_path: &Path, args: &Args) -> Result<(), Error> {
+    let memory_file = ArtifactWriter::create(memory_path, args.compress_artifacts)?;
+    let mut buf_writer = io::BufWriter::with_capacity(args.memory_buffer_size, memory_file);
+
+    match args.memory_format {
+        MemoryFormat::Binary => {
+            let mut memory_writer = FileWriter::new(buf_writer);
+            cairo_run::write_encoded_memory(&cairo_runner.relocated_memory, &mut memory_writer)?;
+            memory_writer.finish()?;
+        }
+        MemoryFormat::Msgpack => {
+            // (address, value) pairs, with the values as 32 little-endian
+            // bytes like in the binary format
+            let memory: Vec<(usize, [u8; 32])> = cairo_runner
+                .relocated_memory
+                .iter()
+                .enumerate()
+                .filter_map(|(i, cell)| cell.map(|felt| (i, felt.to_bytes_le())))
+                .collect();
+            write_msgpack(&mut buf_writer, &memory)?;
+            finish_artifact(buf_writer)?;
+        }
+        MemoryFormat::V2 => {
+            write_memory_v2(&mut buf_writer, &cairo_runner.relocated_memory)?;
+            finish_artifact(buf_writer)?;
+        }
+    }
+    Ok(())
+}
+
+// Writes the memory in the v2 format: a little-endian header (magic, u32
+// version, u64 entry count, u32 word size) followed by the entries of the
+// binary format, each a u64 address and a 32-byte little-endian value.
+fn write_memory_v2(
+    mut writer: impl Write,
+    relocated_memory: &[Option<Felt252>],
+) -> Result<(), Error> {
+    let entries: Vec<(usize, Felt252)> = relocated_memory
+        .iter()
+        .enumerate()
+        .filter_map(|(i, cell)| cell.map(|felt| (i, felt)))
+        .collect();
+    writer.write_all(MEMORY_V2_MAGIC)?;
+    writer.write_all(&MEMORY_V2_VERSION.to_le_bytes())?;
+    writer.write_all(&(entries.len() as u64).to_le_bytes())?;
+    writer.write_all(&MEMORY_V2_WORD_SIZE.to_le_bytes())?;
+    for (addr, felt) in entries {
+        writer.write_all(&(addr as u64).to_le_bytes())?;
+        writer.write_all(&felt.to_bytes_le())?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+// Writes the Cairo PIE zip archive like CairoPie::write_zip_file, with the
+// files compressed as requested.
+fn write_cairo_pie(
+    cairo_pie: &CairoPie,
+    file_path: &Path,
+    compression: PieCompression,
+) -> Result<(), Error> {
+    let mut zip_writer = ZipWriter::new(std::fs::File::create(file_path)?);
+    let options = compression.file_options();
+    zip_writer.start_file("version.json", options)?;
+    serde_json::to_writer(&mut zip_writer, &cairo_pie.version)?;
+    zip_writer.start_file("metadata.json", options)?;
+    serde_json::to_writer(&mut zip_writer, &cairo_pie.metadata)?;
+    zip_writer.start_file("memory.bin", options)?;
+    zip_writer.write_all(&cairo_pie.memory.to_bytes())?;
+    zip_writer.start_file("additional_data.json", options)?;
+    serde_json::to_writer(&mut zip_writer, &cairo_pie.additional_data)?;
+    zip_writer.start_file("execution_resources.json", options)?;
+    serde_json::to_writer(&mut zip_writer, &cairo_pie.execution_resources)?;
+    zip_writer.finish()?;
+    Ok(())
+}
+
+// Writes whatever trace and memory the VM produced before a failed run, for
+// --best_effort. Failures here are ignored: the run error is what gets
+// reported.
+fn write_partial_artifacts(cairo_runner: &mut CairoRunner, vm: &mut VirtualMachine, args: &Args) {
+    if cairo_runner.relocate(vm, true).is_err() {
+        return;
+    }
+    if let Some(ref trace_path) = args.trace_file {
+        let _ = write_trace(cairo_runner, trace_path, args);
+    }
+    if let Some(ref memory_path) = args.memory_file {
+        let _ = write_memory(cairo_runner, memory_path, args);
+    }
+}
+
+// Reads the program input given with --program_input or
+// --program_input_base64, adding the variables of each --program_input_ns
+// file under its prefix.
+fn load_program_input(args: &Args) -> Result<ProgramInput, Error> {
+    let read_file = |file: &PathBuf| -> Result<String, Error> {
+        let input = std::fs::read_to_string(file)?;
+        // An empty file is almost certainly a mistake, so report it instead
+        // of running with no input or failing with a bare parse error.
+        if input.trim().is_empty() {
+            return Err(Error::EmptyProgramInput(file.clone()));
+        }
+        Ok(input)
+    };
+    let mut program_input = if let Some(ref file) = args.program_input {
+        parse_program_input(args, &read_file(file)?, args.input_format)?
+    } else if let Some(ref encoded) = args.program_input_base64 {
+        parse_program_input(args, &decode_base64_input(encoded)?, InputFormat::Json)?
+    } else {
+        ProgramInput::new(HashMap::new())
+    };
+    for (prefix, file) in args.program_input_ns.iter() {
+        let input =
+            ProgramInput::from_str_with_options(read_file(file)?.as_str(), &input_options(args))?;
+        program_input
+            .insert_namespace(prefix, input)
+            .map_err(Error::DuplicateInputKey)?;
+    }
+    Ok(program_input)
+}
+
+// The options for reading the program input given in `args`.
+fn input_options(args: &Args) -> InputOptions {
+    InputOptions {
+        format: args.input_format,
+        resolve_env: args.resolve_env,
+        validation: args.felt_input_validation,
+        range_modulus: args
+            .input_range_modulus
+            .clone()
+            .unwrap_or_else(program_input::field_prime),
+    }
+}
+
+// Parses the main program input, written in `format`, after validating it
+// against the --input_schema file, if any.
+fn parse_program_input(
+    args: &Args,
+    input: &str,
+    format: InputFormat,
+) -> Result<ProgramInput, Error> {
+    check_input_schema(args, input, format)?;
+    let options = InputOptions {
+        format,
+        ..input_options(args)
+    };
+    Ok(ProgramInput::from_str_with_options(input, &options)?)
+}
+
+// Validates the program input against the --input_schema file, if any.
+fn check_input_schema(args: &Args, input: &str, format: InputFormat) -> Result<(), Error> {
+    if let Some(ref path) = args.input_schema {
+        let schema = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+        program_input::validate_schema(input, format, &schema)
+            .map_err(|errors| Error::InputSchema(errors.join("; ")))?;
+    }
+    Ok(())
+}
+
+// Decodes the JSON program input given with --program_input_base64.
+fn decode_base64_input(encoded: &str) -> Result<String, Error> {
+    let bytes =
+        Base64::decode_vec(encoded.trim()).map_err(|e| Error::ProgramInputBase64(e.to_string()))?;
+    String::from_utf8(bytes)
+        .map_err(|_| Error::ProgramInputBase64("the decoded input is not valid UTF-8".into()))
+}
+
+// Summarizes the wall times of repeated runs.
+fn format_timings(timings: &[Duration]) -> String {
+    let min = timings.iter().min().copied().unwrap_or_default();
+    let max = timings.iter().max().copied().unwrap_or_default();
+    let mean = timings.iter().sum::<Duration>() / timings.len().max(1) as u32;
+    format!(
+        "{} runs: min {:?}, mean {:?}, max {:?}",
+        timings.len(),
+        min,
+        mean,
+        max
+    )
+}
+
+pub fn run_cli(args: impl Iterator<Item = String>) -> Result<(), Error> {
+    run_args(config::parse_args(args)?)
+}
+
+// Like run_cli, but with the arguments already parsed.
+pub fn run_args(args: Args) -> Result<(), Error> {
+    if args.program_info {
+        print!("{}", program_info(&std::fs::read(&args.filename)?)?);
+        return Ok(());
+    }
+    if args.print_program_hash {
+        println!("{}", program_hash(&std::fs::read(&args.filename)?)?);
+        return Ok(());
+    }
+    if args.entrypoint_list {
+        for (name, pc) in list_entrypoints(&std::fs::read(&args.filename)?)? {
+            println!("{name}: {pc}");
+        }
+        return Ok(());
+    }
+    if args.list_hints {
+        let hints = collect_hints(&std::fs::read(&args.filename)?)?;
+        print!("{}", format_hint_census(&hints));
+        return Ok(());
+    }
+    if args.dry_run_hints {
+        let unsupported = unsupported_hints(&std::fs::read(&args.filename)?)?;
+        for (pc, code) in unsupported.iter() {
+            println!("pc {pc}: {code}");
+        }
+        if !unsupported.is_empty() {
+            return Err(Error::UnsupportedHints(unsupported.len()));
+        }
+        return Ok(());
+    }
+    let program_input = load_program_input(&args)?;
+    if args.benchmark_layouts {
+        let program_content = std::fs::read(&args.filename)?;
+        let benchmarks = benchmark_layouts(&program_content, &args, program_input)?;
+        print!("{}", format_layout_benchmarks(&benchmarks));
+        return Ok(());
+    }
+    let print_output = args.print_output;
+    let print_builtin_counts = args.print_builtin_counts;
+    // the JSON error carries its own message
+    let report_context = args.error_format == ErrorFormat::Text;
+    let context = format!(
+        "error running {} (entrypoint {})",
+        args.filename.display(),
+        args.entrypoint
+    );
+    match run_timed(args, program_input) {
+        Ok(result) => {
+            if print_output {
+                print!("{}", result.output);
+            }
+            if result.timings.len() > 1 {
+                eprintln!("{}", format_timings(&result.timings));
+            }
+            if let Some(summary) = result.trace_summary() {
+                eprint!("{summary}");
+            }
+            if print_builtin_counts {
+                eprint!("{}", result.format_builtin_counts());
+            }
+            Ok(())
+        }
+        Err(Error::Runner(error)) => {
+            if report_context {
+                eprintln!("{context}: {error}");
+            }
+            Err(Error::Runner(error))
+        }
+        Err(Error::PartialOutput { output, error }) => {
+            if print_output {
+                print!("{output}");
+            }
+            if report_context {
+                eprintln!("{context}: {error}");
+            }
+            Err(Error::PartialOutput { output, error })
+        }
+        Err(err) => Err(err),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::too_many_arguments)]
+    use super::*;
+    use assert_matches::assert_matches;
+    use rstest::rstest;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    // A fresh directory for the files written by a test, removed with them
+    // when dropped, so that the tests running in parallel do not share files
+    // and none is left behind, even by a failing test.
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new() -> Self {
+            static COUNTER: AtomicUsize = AtomicUsize::new(0);
+            let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+            let path = std::env::temp_dir()
+                .join(format!("juvix_cairo_vm_test_{}_{n}", std::process::id()));
+            std::fs::create_dir_all(&path).unwrap();
+            TempDir(path)
+        }
+
+        fn join(&self, name: impl AsRef<Path>) -> PathBuf {
+            self.0.join(name)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[rstest]
+    #[case([].as_slice())]
+    #[case(["juvix-cairo-vm"].as_slice())]
+    fn test_run_missing_mandatory_args(#[case] args: &[&str]) {
+        let args = args.iter().cloned().map(String::from);
+        assert_matches!(run_cli(args), Err(Error::Cli(_)));
+    }
+
+    #[rstest]
+    #[case(["juvix-cairo-vm", "--layout", "broken_layout", "../tests/fibonacci.json"].as_slice())]
+    fn test_run_invalid_args(#[case] args: &[&str]) {
+        let args = args.iter().cloned().map(String::from);
+        assert_matches!(run_cli(args), Err(Error::Cli(_)));
+    }
+
+    #[rstest]
+    #[case(&[])]
+    #[case(&["--trace_file", "/dev/null"])]
+    #[case(&["--memory_file", "/dev/null"])]
+    fn test_run_air_private_input_temp_files(#[case] files: &[&str]) {
+        let dir = TempDir::new();
+        let path = dir.join("private_input.json");
+        let mut args = vec![
+            "juvix-cairo-vm",
+            "tests/proof_programs/fibonacci.json",
+            "--proof_mode",
+            "--air_private_input",
+            path.to_str().unwrap(),
+        ];
+        args.extend_from_slice(files);
+        assert_matches!(run_cli(args.into_iter().map(String::from)), Ok(()));
+        let json: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        for (key, kind) in [("trace_path", "trace"), ("memory_path", "memory")] {
+            let artifact = Path::new(json[key].as_str().unwrap());
+            if artifact != Path::new("/dev/null") {
+                // written next to the private input
+                assert_eq!(artifact, dir.join(format!("private_input.json.{kind}")));
+                assert!(std::fs::metadata(artifact).unwrap().len() > 0);
+            }
+        }
+    }
+
+    #[rstest]
+    #[case(["juvix-cairo-vm", "tests/fibonacci.json", "--air_private_input", "/dev/null", "--trace_file", "/dev/null", "--memory_file", "/dev/null"].as_slice())]
+    fn test_run_air_private_input_no_proof(#[case] args: &[&str]) {
+        let args = args.iter().cloned().map(String::from);
+        assert_matches!(run_cli(args), Err(Error::Cli(_)));
+    }
+
+    #[rstest]
+    fn test_run_ok(
+        #[values(None,
+                 Some("plain"),
+                 Some("small"),
+                 Some("dex"),
+                 Some("starknet"),
+                 Some("starknet_with_keccak"),
+                 Some("recursive_large_output"),
+                 Some("all_cairo"),
+                 Some("all_solidity"),
+                 //FIXME: dynamic layout leads to _very_ slow execution
+                 //Some("dynamic"),
+        )]
+        layout: Option<&str>,
+        #[values(false, true)] mut memory_file: bool,
+        #[values(false, true)] mut trace_file: bool,
+        #[values(false, true)] proof_mode: bool,
+        #[values(false, true)] print_output: bool,
+        #[values(false, true)] entrypoint: bool,
+        #[values(false, true)] air_public_input: bool,
+        #[values(false, true)] air_private_input: bool,
+        #[values(false, true)] cairo_pie_output: bool,
+    ) {
+        let mut args = vec!["juvix-cairo-vm".to_string()];
+        if let Some(layout) = layout {
+            args.extend_from_slice(&["--layout".to_string(), layout.to_string()]);
+        }
+        if air_public_input {
+            args.extend_from_slice(&["--air_public_input".to_string(), "/dev/null".to_string()]);
+        }
+        if air_private_input {
+            // the memory file would otherwise be written next to /dev/null
+            memory_file = true;
+            args.extend_from_slice(&["--air_private_input".to_string(), "/dev/null".to_string()]);
+        }
+        if cairo_pie_output {
+            args.extend_from_slice(&["--cairo_pie_output".to_string(), "/dev/null".to_string()]);
+        }
+        if proof_mode {
+            trace_file = true;
+            args.extend_from_slice(&["--proof_mode".to_string()]);
+        }
+        if entrypoint {
+            args.extend_from_slice(&["--entrypoint".to_string(), "main".to_string()]);
+        }
+        if memory_file {
+            args.extend_from_slice(&["--memory_file".to_string(), "/dev/null".to_string()]);
+        }
+        if trace_file {
+            args.extend_from_slice(&["--trace_file".to_string(), "/dev/null".to_string()]);
+        }
+        if print_output {
+            args.extend_from_slice(&["--print_output".to_string()]);
+        }
+
+        args.push("tests/proof_programs/fibonacci.json".to_string());
+        if air_public_input && !proof_mode
+            || (air_private_input && !proof_mode)
+            || cairo_pie_output && proof_mode
+        {
+            assert_matches!(run_cli(args.into_iter()), Err(_));
+        } else {
+            assert_matches!(run_cli(args.into_iter()), Ok(_));
+        }
+    }
+
+    #[test]
+    fn test_run_missing_program() {
+        let args = ["juvix-cairo-vm", "missing/program.json"]
+            .into_iter()
+            .map(String::from);
+        assert_matches!(run_cli(args), Err(Error::IO(_)));
+    }
+
+    #[rstest]
+    #[case("tests/manually_compiled/invalid_even_length_hex.json")]
+    #[case("tests/manually_compiled/invalid_memory.json")]
+    #[case("tests/manually_compiled/invalid_odd_length_hex.json")]
+    #[case("tests/manually_compiled/no_data_program.json")]
+    #[case("tests/manually_compiled/no_main_program.json")]
+    fn test_run_bad_file(#[case] program: &str) {
+        let args = ["juvix-cairo-vm", program].into_iter().map(String::from);
+        assert_matches!(run_cli(args), Err(Error::Runner(_)));
+    }
+
+    #[test]
+    fn test_valid_layouts() {
+        let valid_layouts = vec![
+            "plain",
+            "small",
+            "dex",
+            "starknet",
+            "starknet_with_keccak",
+            "recursive_large_output",
+            "all_cairo",
+            "all_solidity",
+        ];
+
+        for layout in valid_layouts {
+            assert_eq!(validate_layout(layout), Ok(layout.to_string()));
+        }
+    }
+
+    #[test]
+    fn test_invalid_layout() {
+        let invalid_layout = "invalid layout name";
+        assert!(validate_layout(invalid_layout).is_err());
+    }
+
+    #[rstest]
+    #[case("tests/input1.json", "tests/input1_input.json")]
+    fn test_input_positive(#[case] program: &str, #[case] input: &str) {
+        let args = [
+            "juvix-cairo-vm",
+            program,
+            "--program_input",
+            input,
+            "--proof_mode",
+            "--layout",
+            "small",
+        ]
+        .into_iter()
+        .map(String::from);
+        assert_matches!(run_cli(args), Ok(()));
+    }
+
+    #[rstest]
+    #[case("tests/empty_input.json")]
+    #[case("tests/whitespace_input.json")]
+    fn test_input_empty(#[case] input: &str) {
+        let args = [
+            "juvix-cairo-vm",
+            "tests/input1.json",
+            "--program_input",
+            input,
+        ]
+        .into_iter()
+        .map(String::from);
+        assert_matches!(run_cli(args), Err(Error::EmptyProgramInput(path)) if path == Path::new(input));
+    }
+
+    #[rstest]
+    #[case("tests/input1.json", "tests/input1_bad_input.json")]
+    fn test_input_negative(#[case] program: &str, #[case] input: &str) {
+        let args = [
+            "juvix-cairo-vm",
+            program,
+            "--program_input",
+            input,
+            "--proof_mode",
+            "--layout",
+            "small",
+        ]
+        .into_iter()
+        .map(String::from);
+        assert_matches!(run_cli(args), Err(Error::Runner(_)));
+    }
+
+    #[rstest]
+    #[case("tests/input2.json", "tests/input2_input.json", "83\n")]
+    #[case("tests/input3.json", "tests/input3_input.json", "14\n")]
+    #[case("tests/input4.json", "tests/input4_input.json", "4\n16\n9\n")]
+    fn test_input_output_positive(
+        #[case] program: &str,
+        #[case] input: &str,
+        #[case] output: &str,
+    ) {
+        let args_cli = [
+            "juvix-cairo-vm",
+            program,
+            "--program_input",
+            input,
+            "--proof_mode",
+            "--layout",
+            "small",
+        ]
+        .into_iter()
+        .map(String::from);
+        let program_input =
+            ProgramInput::from_json(std::fs::read_to_string(input).unwrap().as_str()).unwrap();
+        let args = Args::try_parse_from(args_cli).unwrap();
+        assert_eq!(run(args, program_input).unwrap(), output);
+    }
+
+    #[rstest]
+    #[case("tests/input4_input.jsonc", "jsonc")]
+    #[case("tests/input4_input.toml", "toml")]
+    fn test_input_format(#[case] input: &str, #[case] format: &str) {
+        let args = [
+            "juvix-cairo-vm",
+            "tests/input4.json",
+            "--program_input",
+            input,
+            "--input_format",
+            format,
+            "--proof_mode",
+            "--layout",
+            "small",
+        ]
+        .into_iter()
+        .map(String::from);
+        assert_matches!(run_cli(args), Ok(()));
+    }
+
+    #[rstest]
+    #[case("tests/ec_random.json", None)]
+    #[case("tests/input4.json", Some("tests/input4_input.json"))]
+    fn test_anoma_cairo_vm_runner_deterministic(
+        #[case] program: &str,
+        #[case] input: Option<&str>,
+    ) {
+        let program = std::fs::read(program).unwrap();
+        let run = || {
+            let program_input = match input {
+                Some(path) => {
+                    ProgramInput::from_json(&std::fs::read_to_string(path).unwrap()).unwrap()
+                }
+                None => ProgramInput::new(HashMap::new()),
+            };
+            anoma_cairo_vm_runner(&program, program_input).unwrap()
+        };
+        assert_eq!(run(), run());
+    }
+
+    #[test]
+    fn test_anoma_cairo_vm_runner_seeded() {
+        let program = std::fs::read("tests/ec_random.json").unwrap();
+        let run = |seed| {
+            let config = AnomaRunConfig { seed: Some(seed) };
+            anoma_cairo_vm_runner_with_felts(&program, ProgramInput::new(HashMap::new()), &config)
+                .unwrap()
+        };
+        let (_, _, trace, memory, _) = run(7);
+        let (_, _, trace_again, memory_again, _) = run(7);
+        assert_eq!(trace, trace_again);
+        assert_eq!(memory, memory_again);
+        let (_, _, _, other_memory, _) = run(8);
+        assert_ne!(memory, other_memory);
+    }
+
+    #[test]
+    fn test_anoma_cairo_vm_runner_with_felts() {
+        let program = std::fs::read("tests/input4.json").unwrap();
+        let input =
+            ProgramInput::from_json(&std::fs::read_to_string("tests/input4_input.json").unwrap())
+                .unwrap();
+        let (output, felts, ..) =
+            anoma_cairo_vm_runner_with_felts(&program, input, &AnomaRunConfig::default()).unwrap();
+        assert_eq!(
+            felts,
+            [Felt252::from(4), Felt252::from(16), Felt252::from(9)]
+        );
+        let formatted: Vec<String> = felts.iter().map(|felt| felt.to_string()).collect();
+        assert_eq!(output.lines().collect::<Vec<_>>(), formatted);
+    }
+
+    #[test]
+    fn test_anoma_cairo_vm_runner_typed() {
+        let program = std::fs::read("tests/input4.json").unwrap();
+        let input =
+            ProgramInput::from_json(&std::fs::read_to_string("tests/input4_input.json").unwrap())
+                .unwrap();
+        let (output, trace_bytes, memory_bytes, _) =
+            anoma_cairo_vm_runner(&program, input.clone()).unwrap();
+        let (typed_output, trace, memory) =
+            anoma_cairo_vm_runner_typed(&program, input, &AnomaRunConfig::default()).unwrap();
+        assert_eq!(output, typed_output);
+
+        let decoded_trace: Vec<RelocatedTraceEntry> = trace_bytes
+            .chunks_exact(24)
+            .map(|entry| {
+                let word = |i: usize| {
+                    u64::from_le_bytes(entry[8 * i..8 * (i + 1)].try_into().unwrap()) as usize
+                };
+                RelocatedTraceEntry {
+                    ap: word(0),
+                    fp: word(1),
+                    pc: word(2),
+                }
+            })
+            .collect();
+        assert_eq!(decoded_trace, trace);
+
+        let decoded_memory: Vec<(usize, Felt252)> = memory_bytes
+            .chunks_exact(40)
+            .map(|cell| {
+                (
+                    u64::from_le_bytes(cell[..8].try_into().unwrap()) as usize,
+                    Felt252::from_bytes_le_slice(&cell[8..]),
+                )
+            })
+            .collect();
+        let typed_memory: Vec<(usize, Felt252)> = memory
+            .iter()
+            .enumerate()
+            .filter_map(|(i, cell)| cell.map(|felt| (i, felt)))
+            .collect();
+        assert_eq!(decoded_memory, typed_memory);
+    }
+
+    #[rstest]
+    #[case(FeltOutputFormat::Dec, "<missing>\n-1\n2:3\n")]
+    #[case(
+        FeltOutputFormat::Hex,
+        "<missing>\n0x800000000000011000000000000000000000000000000000000000000000000\n2:3\n"
+    )]
+    fn test_format_output_cells(#[case] format: FeltOutputFormat, #[case] expected: &str) {
+        let cells = [
+            None,
+            Some(Felt252::from(-1).into()),
+            Some(Relocatable::from((2, 3)).into()),
+        ];
+        assert_eq!(
+            format_output(&cells, format, None),
+            (expected.to_string(), false)
+        );
+    }
+
+    #[test]
+    fn test_emit_output_felts_holes() {
+        let dir = TempDir::new();
+        let output_path = dir.join("output_felts.json");
+        let args = [
+            "juvix-cairo-vm",
+            "tests/unfilled_output.json",
+            "--layout",
+            "small",
+            "--emit_output_felts",
+            output_path.to_str().unwrap(),
+        ]
+        .into_iter()
+        .map(String::from);
+        assert_matches!(run_cli(args), Ok(()));
+        let output: Vec<Option<String>> =
+            serde_json::from_str(&std::fs::read_to_string(&output_path).unwrap()).unwrap();
+        assert_eq!(output, [None, Some("7".to_string())]);
+    }
+
+    #[test]
+    fn test_emit_output_felts() {
+        let dir = TempDir::new();
+        let output_path = dir.join("output_felts.json");
+        let args = [
+            "juvix-cairo-vm",
+            "tests/input4.json",
+            "--program_input",
+            "tests/input4_input.json",
+            "--proof_mode",
+            "--layout",
+            "small",
+            "--emit_output_felts",
+            output_path.to_str().unwrap(),
+        ]
+        .into_iter()
+        .map(String::from);
+        assert_matches!(run_cli(args), Ok(()));
+        let output: Vec<String> =
+            serde_json::from_str(&std::fs::read_to_string(&output_path).unwrap()).unwrap();
+        assert_eq!(output, ["4", "16", "9"]);
+    }
+
+    #[rstest]
+    #[case("--felt_output_format", "dec", "83\n")]
+    #[case("--felt_output_format", "hex", "0x53\n")]
+    #[case(
+        "--felt_output_format",
+        "bytes_le",
+        "5300000000000000000000000000000000000000000000000000000000000000\n"
+    )]
+    #[case("--output_radix", "hex", "0x53\n")]
+    fn test_felt_output_format(#[case] flag: &str, #[case] format: &str, #[case] output: &str) {
+        let args_cli = [
+            "juvix-cairo-vm",
+            "tests/input2.json",
+            "--program_input",
+            "tests/input2_input.json",
+            "--proof_mode",
+            "--layout",
+            "small",
+            flag,
+            format,
+        ]
+        .into_iter()
+        .map(String::from);
+        let program_input = ProgramInput::from_json(
+            std::fs::read_to_string("tests/input2_input.json")
+                .unwrap()
+                .as_str(),
+        )
+        .unwrap();
+        let args = Args::try_parse_from(args_cli).unwrap();
+        assert_eq!(run(args, program_input).unwrap(), output);
+    }
+
+    #[rstest]
+    #[case(Felt252::from(83), FeltOutputFormat::Dec, "83")]
+    #[case(Felt252::from(-1), FeltOutputFormat::Dec, "-1")]
+    #[case(Felt252::from(-1), FeltOutputFormat::Hex, "0x800000000000011000000000000000000000000000000000000000000000000")]
+    #[case(
+        Felt252::from(0x1234),
+        FeltOutputFormat::BytesLe,
+        "3412000000000000000000000000000000000000000000000000000000000000"
+    )]
+    fn test_format_felt(
+        #[case] felt: Felt252,
+        #[case] format: FeltOutputFormat,
+        #[case] expected: &str,
+    ) {
+        assert_eq!(format_felt(&felt, format), expected);
+    }
+
+    #[rstest]
+    #[case(false)]
+    #[case(true)]
+    fn test_builtin_hints(#[case] enabled: bool) {
+        let mut args = vec!["juvix-cairo-vm", "tests/builtin_hint.json"];
+        if enabled {
+            args.push("--builtin_hints");
+        }
+        let args = Args::try_parse_from(args).unwrap();
+        let result = run(args, ProgramInput::new(HashMap::new()));
+        if enabled {
+            assert_matches!(result, Ok(_));
+        } else {
+            assert_matches!(result, Err(Error::Runner(_)));
+        }
+    }
+
+    #[test]
+    fn test_buffer_sizes() {
+        let dir = TempDir::new();
+        let run_with = |name: &str, buffer_size: &str| {
+            let trace_path = dir.join(format!("juvix_cairo_vm_test_{name}.trace"));
+            let memory_path = dir.join(format!("juvix_cairo_vm_test_{name}.memory"));
+            let args = [
+                "juvix-cairo-vm",
+                "tests/fibonacci.json",
+                "--trace_file",
+                trace_path.to_str().unwrap(),
+                "--memory_file",
+                memory_path.to_str().unwrap(),
+                "--trace_buffer_size",
+                buffer_size,
+                "--memory_buffer_size",
+                buffer_size,
+            ]
+            .into_iter()
+            .map(String::from);
+            assert_matches!(run_cli(args), Ok(()));
+            let files = (
+                std::fs::read(&trace_path).unwrap(),
+                std::fs::read(&memory_path).unwrap(),
+            );
+            files
+        };
+        let (trace, memory) = run_with("large_buffer", "1048576");
+        assert!(!trace.is_empty());
+        assert_eq!(run_with("small_buffer", "7"), (trace, memory));
+    }
+
+    #[test]
+    fn test_get_memory_holes() {
+        let one = Some(Felt252::ONE);
+        assert_eq!(get_memory_holes(&[None, one, None, one, None]), [2, 4]);
+        assert_eq!(get_memory_holes(&[None, one]), Vec::<usize>::new());
+    }
+
+    #[rstest]
+    #[case("tests/ec_random.json", true)]
+    #[case("tests/proof_programs/fibonacci.json", false)]
+    fn test_memory_holes_json(#[case] program: &str, #[case] has_holes: bool) {
+        let dir = TempDir::new();
+        let holes_path = dir.join("holes.json");
+        let args = [
+            "juvix-cairo-vm",
+            program,
+            "--proof_mode",
+            "--layout",
+            "small",
+            "--memory_holes_json",
+            holes_path.to_str().unwrap(),
+        ]
+        .into_iter()
+        .map(String::from);
+        assert_matches!(run_cli(args), Ok(()));
+        let holes: Vec<usize> =
+            serde_json::from_str(&std::fs::read_to_string(&holes_path).unwrap()).unwrap();
+        assert_eq!(!holes.is_empty(), has_holes);
+        assert!(holes.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn test_missing_builtins() {
+        let args = [
+            "juvix-cairo-vm",
+            "tests/input2.json",
+            "--program_input",
+            "tests/input2_input.json",
+        ]
+        .into_iter()
+        .map(String::from);
+        let error = run_cli(args).unwrap_err();
+        assert_matches!(
+            error,
+            Error::MissingBuiltins { ref builtins, ref layout }
+                if builtins == "output" && layout == "plain"
+        );
+        assert!(error.to_string().contains("--allow_missing_builtins"));
+    }
+
+    #[test]
+    fn test_all_cairo_allows_missing_builtins() {
+        // main{add_mod_ptr}(), while add_mod is not part of the all_cairo layout
+        let program = std::fs::read_to_string("tests/add_mod_builtin.json").unwrap();
+        let config = |allow_missing_builtins| Config {
+            layout: Some("all_cairo".to_string()),
+            allow_missing_builtins,
+            ..Default::default()
+        };
+        run_json(&program, "{}", config(None)).unwrap();
+        assert_matches!(
+            run_json(&program, "{}", config(Some(false))),
+            Err(Error::MissingBuiltins { ref builtins, .. }) if builtins == "add_mod"
+        );
+    }
+
+    #[rstest]
+    #[case(10, false)]
+    #[case(1000, true)]
+    fn test_max_memory_cells(#[case] limit: usize, #[case] ok: bool) {
+        let args = [
+            "juvix-cairo-vm",
+            "tests/fibonacci.json",
+            "--max_memory_cells",
+            &limit.to_string(),
+        ]
+        .map(String::from)
+        .into_iter();
+        match run_cli(args) {
+            Ok(()) => assert!(ok),
+            Err(Error::MemoryLimitExceeded { used, limit: max }) => {
+                assert!(!ok);
+                assert_eq!(max, limit);
+                assert!(used > limit);
+            }
+            Err(err) => panic!("unexpected error: {err}"),
+        }
+    }
+
+    #[test]
+    fn test_cairo_pie_compression() {
+        let dir = TempDir::new();
+        let write_pie = |compression: &str| {
+            let path = dir.join(format!("juvix_cairo_vm_test_{compression}.pie.zip"));
+            let args = [
+                "juvix-cairo-vm",
+                "tests/fibonacci.json",
+                "--cairo_pie_output",
+                path.to_str().unwrap(),
+                "--cairo_pie_compression",
+                compression,
+            ]
+            .into_iter()
+            .map(String::from);
+            assert_matches!(run_cli(args), Ok(()));
+            let size = std::fs::metadata(&path).unwrap().len();
+            let cairo_pie = CairoPie::read_zip_file(&path).unwrap();
+            (size, cairo_pie)
+        };
+        let (default_size, default_pie) = write_pie("default");
+        let (store_size, store_pie) = write_pie("store");
+        assert!(store_size > default_size);
+        assert_eq!(store_pie, default_pie);
+    }
+
+    #[test]
+    fn test_compress_artifacts() {
+        let dir = TempDir::new();
+        let run_with = |compress: bool| {
+            let trace_path = dir.join(format!("juvix_cairo_vm_test_{compress}.trace"));
+            let memory_path = dir.join(format!("juvix_cairo_vm_test_{compress}.memory"));
+            let mut args = vec![
+                "juvix-cairo-vm",
+                "tests/fibonacci.json",
+                "--trace_file",
+                trace_path.to_str().unwrap(),
+                "--memory_file",
+                memory_path.to_str().unwrap(),
+            ];
+            if compress {
+                args.push("--compress_artifacts");
+            }
+            assert_matches!(run_cli(args.into_iter().map(String::from)), Ok(()));
+            let read = |path: &Path| {
+                let path = artifact_path(path, compress);
+                let mut contents = Vec::new();
+                if compress {
+                    let file = std::fs::File::open(&path).unwrap();
+                    io::Read::read_to_end(&mut flate2::read::GzDecoder::new(file), &mut contents)
+                        .unwrap();
+                } else {
+                    contents = std::fs::read(&path).unwrap();
+                }
+                contents
+            };
+            (read(&trace_path), read(&memory_path))
+        };
+        let (trace, memory) = run_with(false);
+        assert!(!trace.is_empty());
+        assert_eq!(run_with(true), (trace, memory));
+    }
 
-        args.push("tests/proof_programs/fibonacci.json".to_string());
-        if air_public_input && !proof_mode
-            || (air_private_input && (!proof_mode || !trace_file || !memory_file))
-            || cairo_pie_output && proof_mode
-        {
-            assert_matches!(run_cli(args.into_iter()), Err(_));
-        } else {
-            assert_matches!(run_cli(args.into_iter()), Ok(_));
+    #[test]
+    fn test_json_trace() {
+        let dir = TempDir::new();
+        let run_with = |extra: &[&str]| {
+            let trace_path = dir.join(format!("juvix_cairo_vm_test{}.json", extra.len()));
+            let args = [
+                "juvix-cairo-vm",
+                "tests/fibonacci.json",
+                "--trace_file",
+                trace_path.to_str().unwrap(),
+                "--trace_format",
+                "json",
+            ]
+            .into_iter()
+            .chain(extra.iter().copied())
+            .map(String::from);
+            assert_matches!(run_cli(args), Ok(()));
+            let trace: Vec<serde_json::Value> =
+                serde_json::from_slice(&std::fs::read(&trace_path).unwrap()).unwrap();
+            trace
+        };
+        let trace = run_with(&[]);
+        let enriched = run_with(&["--trace_include_registers"]);
+        assert!(trace.len() > 3);
+        assert_eq!(trace.len(), enriched.len());
+        assert!(trace[0].get("opcode").is_none());
+        for (entry, enriched) in trace.iter().zip(&enriched).take(5) {
+            for register in ["pc", "ap", "fp"] {
+                assert!(entry[register].is_u64());
+                assert_eq!(entry[register], enriched[register]);
+            }
+            let opcode = enriched["opcode"].as_str().unwrap();
+            assert!(["call", "ret", "assert_eq", "jmp", "jnz", "ap_add", "nop"].contains(&opcode));
         }
+        // the run ends with the return from main
+        assert_eq!(enriched.last().unwrap()["opcode"], "ret");
     }
 
     #[test]
-    fn test_run_missing_program() {
-        let args = ["juvix-cairo-vm", "missing/program.json"]
+    fn test_msgpack_artifacts() {
+        let dir = TempDir::new();
+        let run_with = |format: &str| {
+            let trace_path = dir.join(format!("juvix_cairo_vm_test.{format}.trace"));
+            let memory_path = dir.join(format!("juvix_cairo_vm_test.{format}.memory"));
+            let args = [
+                "juvix-cairo-vm",
+                "tests/fibonacci.json",
+                "--trace_file",
+                trace_path.to_str().unwrap(),
+                "--memory_file",
+                memory_path.to_str().unwrap(),
+                "--trace_format",
+                format,
+                "--memory_format",
+                format,
+            ]
             .into_iter()
             .map(String::from);
-        assert_matches!(run_cli(args), Err(Error::IO(_)));
+            assert_matches!(run_cli(args), Ok(()));
+            let files = (
+                std::fs::read(&trace_path).unwrap(),
+                std::fs::read(&memory_path).unwrap(),
+            );
+            files
+        };
+        let (trace, memory) = run_with("binary");
+        let (msgpack_trace, msgpack_memory) = run_with("msgpack");
+
+        let word = |bytes: &[u8]| u64::from_le_bytes(bytes.try_into().unwrap()) as usize;
+        let trace: Vec<RelocatedTraceEntry> = trace
+            .chunks(24)
+            .map(|entry| RelocatedTraceEntry {
+                ap: word(&entry[0..8]),
+                fp: word(&entry[8..16]),
+                pc: word(&entry[16..24]),
+            })
+            .collect();
+        let memory: Vec<(usize, [u8; 32])> = memory
+            .chunks(40)
+            .map(|cell| (word(&cell[0..8]), cell[8..40].try_into().unwrap()))
+            .collect();
+        assert!(!trace.is_empty());
+        assert_eq!(
+            rmp_serde::from_slice::<Vec<RelocatedTraceEntry>>(&msgpack_trace).unwrap(),
+            trace
+        );
+        assert_eq!(
+            rmp_serde::from_slice::<Vec<(usize, [u8; 32])>>(&msgpack_memory).unwrap(),
+            memory
+        );
+    }
+
+    #[test]
+    fn test_run_json() {
+        let program = std::fs::read_to_string("tests/input2.json").unwrap();
+        let input = std::fs::read_to_string("tests/input2_input.json").unwrap();
+        let config = Config {
+            layout: Some("small".to_string()),
+            proof_mode: Some(true),
+            ..Default::default()
+        };
+        let result = run_json(&program, &input, config).unwrap();
+        assert_eq!(result.output, "83\n");
+        assert_eq!(result.output_len(), 1);
+    }
+
+    #[test]
+    fn test_run_json_input_options() {
+        let program = std::fs::read_to_string("tests/input2.json").unwrap();
+        let config = |input_format, felt_input_validation| Config {
+            layout: Some("small".to_string()),
+            proof_mode: Some(true),
+            input_format: Some(input_format),
+            felt_input_validation: Some(felt_input_validation),
+            ..Default::default()
+        };
+        let input = "{\"X\": 9.0, \"Y\": 74, // a comment\n}";
+        assert!(run_json(
+            &program,
+            input,
+            config(InputFormat::Json, FeltInputValidation::Lenient)
+        )
+        .is_err());
+        assert!(run_json(
+            &program,
+            input,
+            config(InputFormat::Jsonc, FeltInputValidation::Strict)
+        )
+        .is_err());
+        let result = run_json(
+            &program,
+            input,
+            config(InputFormat::Jsonc, FeltInputValidation::Lenient),
+        )
+        .unwrap();
+        assert_eq!(result.output, "83\n");
+    }
+
+    #[test]
+    fn test_run_borrowed() {
+        let program = std::fs::read("tests/input2.json").unwrap();
+        let input = std::fs::read_to_string("tests/input2_input.json").unwrap();
+        let program_input = ProgramInput::from_json(&input).unwrap();
+        let args = parse_args(
+            ["juvix-cairo-vm", "-", "--layout", "small", "--proof_mode"]
+                .into_iter()
+                .map(String::from),
+        )
+        .unwrap();
+        for _ in 0..2 {
+            let result = run_borrowed(&program, &args, &program_input).unwrap();
+            assert_eq!(result.output, "83\n");
+        }
     }
 
     #[rstest]
-    #[case("tests/manually_compiled/invalid_even_length_hex.json")]
-    #[case("tests/manually_compiled/invalid_memory.json")]
-    #[case("tests/manually_compiled/invalid_odd_length_hex.json")]
-    #[case("tests/manually_compiled/no_data_program.json")]
-    #[case("tests/manually_compiled/no_main_program.json")]
-    fn test_run_bad_file(#[case] program: &str) {
-        let args = ["juvix-cairo-vm", program].into_iter().map(String::from);
-        assert_matches!(run_cli(args), Err(Error::Runner(_)));
+    #[case(None, "4\n16\n9\n", false)]
+    #[case(Some(7), "4\n16\n9\n", false)]
+    #[case(Some(4), "4\n16", true)]
+    #[case(Some(0), "", true)]
+    fn test_output_cap(
+        #[case] output_cap: Option<usize>,
+        #[case] output: &str,
+        #[case] truncated: bool,
+    ) {
+        let program = std::fs::read_to_string("tests/input4.json").unwrap();
+        let input = std::fs::read_to_string("tests/input4_input.json").unwrap();
+        let config = Config {
+            layout: Some("small".to_string()),
+            proof_mode: Some(true),
+            output_cap,
+            ..Default::default()
+        };
+        let result = run_json(&program, &input, config).unwrap();
+        assert_eq!(result.output, output);
+        assert_eq!(result.output_truncated(), truncated);
+        assert_eq!(result.output_len(), 3);
     }
 
     #[test]
-    fn test_valid_layouts() {
-        let valid_layouts = vec![
-            "plain",
+    fn test_progress_keeps_result() {
+        let program = std::fs::read_to_string("tests/fibonacci.json").unwrap();
+        let run_with = |progress| {
+            let config = Config {
+                progress,
+                ..Default::default()
+            };
+            run_json(&program, "{}", config).unwrap()
+        };
+        let (result, with_progress) = (run_with(None), run_with(Some(true)));
+        assert_eq!(
+            result.output_felt_strings(),
+            with_progress.output_felt_strings()
+        );
+        assert_eq!(result.steps, with_progress.steps);
+    }
+
+    #[test]
+    fn test_builtin_instance_counts() {
+        let program = std::fs::read_to_string("tests/range_check.json").unwrap();
+        let config = Config {
+            layout: Some("small".to_string()),
+            ..Default::default()
+        };
+        let result = run_json(&program, "{}", config).unwrap();
+        let counts = result.builtin_instance_counts();
+        assert_eq!(counts, HashMap::from([("range_check".to_string(), 1)]));
+        assert_eq!(result.format_builtin_counts(), "range_check: 1\n");
+        let args = [
+            "juvix-cairo-vm",
+            "tests/range_check.json",
+            "--layout",
             "small",
-            "dex",
-            "starknet",
-            "starknet_with_keccak",
-            "recursive_large_output",
-            "all_cairo",
-            "all_solidity",
+            "--print_builtin_counts",
         ];
+        assert_matches!(run_cli(args.into_iter().map(String::from)), Ok(()));
+    }
 
-        for layout in valid_layouts {
-            assert_eq!(validate_layout(layout), Ok(layout.to_string()));
-        }
+    #[test]
+    fn test_output_felt_strings() {
+        let program = std::fs::read_to_string("tests/input4.json").unwrap();
+        let input = std::fs::read_to_string("tests/input4_input.json").unwrap();
+        let config = Config {
+            layout: Some("small".to_string()),
+            proof_mode: Some(true),
+            ..Default::default()
+        };
+        let result = run_json(&program, &input, config).unwrap();
+        assert_eq!(result.output_felt_strings(), ["4", "16", "9"]);
+        assert_eq!(result.output, "4\n16\n9\n");
     }
 
     #[test]
-    fn test_invalid_layout() {
-        let invalid_layout = "invalid layout name";
-        assert!(validate_layout(invalid_layout).is_err());
+    fn test_run_proof() {
+        let program = std::fs::read("tests/input2.json").unwrap();
+        let input =
+            ProgramInput::from_json(&std::fs::read_to_string("tests/input2_input.json").unwrap())
+                .unwrap();
+        let config = Config {
+            layout: Some("small".to_string()),
+            ..Default::default()
+        };
+        let proof_inputs = run_proof(&program, input, config).unwrap();
+        let public_input: serde_json::Value =
+            serde_json::from_str(&proof_inputs.public_input).unwrap();
+        assert_eq!(public_input["layout"], "small");
+        assert!(serde_json::from_str::<serde_json::Value>(&proof_inputs.private_input).is_ok());
+        assert!(!proof_inputs.trace.is_empty());
+        assert_eq!(proof_inputs.trace.len() % 24, 0);
+        assert!(!proof_inputs.memory.is_empty());
+        assert_eq!(proof_inputs.memory.len() % 40, 0);
     }
 
     #[rstest]
-    #[case("tests/input1.json", "tests/input1_input.json")]
-    fn test_input_positive(#[case] program: &str, #[case] input: &str) {
+    #[case("{", "{}")]
+    #[case("{}", "[")]
+    fn test_run_json_invalid(#[case] program: &str, #[case] input: &str) {
+        assert!(run_json(program, input, Config::default()).is_err());
+    }
+
+    #[rstest]
+    #[case(&[], None)]
+    #[case(&["--no_output_on_error"], None)]
+    #[case(&["--no_output_on_error", "false"], Some("83\n"))]
+    fn test_no_output_on_error(#[case] extra: &[&str], #[case] partial_output: Option<&str>) {
+        let base = [
+            "juvix-cairo-vm",
+            "tests/output_then_fail.json",
+            "--program_input",
+            "tests/input2_input.json",
+            "--proof_mode",
+            "--layout",
+            "small",
+        ];
+        let args = [&base[..], extra].concat().into_iter().map(String::from);
+        match (run_cli(args), partial_output) {
+            (Err(Error::PartialOutput { output, .. }), Some(expected)) => {
+                assert_eq!(output, expected)
+            }
+            (Err(Error::Runner(_)), None) => (),
+            (result, _) => panic!("unexpected result: {result:?}"),
+        }
+    }
+
+    #[test]
+    fn test_partial_output_with_holes() {
+        let program = std::fs::read_to_string("tests/unfilled_output_then_fail.json").unwrap();
+        let config = Config {
+            layout: Some("small".to_string()),
+            no_output_on_error: Some(false),
+            ..Default::default()
+        };
+        assert_matches!(
+            run_json(&program, r#"{"X": [1]}"#, config),
+            Err(Error::PartialOutput { output, .. }) if output == "<missing>\n7\n"
+        );
+    }
+
+    #[test]
+    fn test_best_effort() {
+        let dir = TempDir::new();
+        let trace_path = dir.join("juvix_cairo_vm_test_best_effort.trace");
+        let memory_path = dir.join("juvix_cairo_vm_test_best_effort.memory");
         let args = [
             "juvix-cairo-vm",
-            program,
+            "tests/output_then_fail.json",
             "--program_input",
-            input,
+            "tests/input2_input.json",
             "--proof_mode",
             "--layout",
             "small",
+            "--best_effort",
+            "--trace_file",
+            trace_path.to_str().unwrap(),
+            "--memory_file",
+            memory_path.to_str().unwrap(),
         ]
         .into_iter()
         .map(String::from);
-        assert_matches!(run_cli(args), Ok(()));
+        assert_matches!(run_cli(args), Err(Error::Runner(_)));
+        let trace = std::fs::read(&trace_path).unwrap();
+        assert!(!trace.is_empty());
+        assert_eq!(trace.len() % 24, 0);
+        assert!(!std::fs::read(&memory_path).unwrap().is_empty());
     }
 
     #[rstest]
-    #[case("tests/input1.json", "tests/input1_bad_input.json")]
-    fn test_input_negative(#[case] program: &str, #[case] input: &str) {
+    #[case("tests/input2_input.json", true)]
+    #[case("tests/input1_input.json", false)]
+    fn test_input_schema(#[case] input: &str, #[case] valid: bool) {
         let args = [
             "juvix-cairo-vm",
-            program,
+            "tests/input2.json",
+            "--layout",
+            "small",
             "--program_input",
             input,
-            "--proof_mode",
+            "--input_schema",
+            "tests/input2_schema.json",
+        ]
+        .into_iter()
+        .map(String::from);
+        match run_cli(args) {
+            Ok(()) => assert!(valid),
+            Err(Error::InputSchema(msg)) => {
+                assert!(!valid);
+                assert!(msg.contains("\"X\" is a required property"));
+            }
+            Err(err) => panic!("unexpected error: {err}"),
+        }
+    }
+
+    #[test]
+    fn test_program_input_base64() {
+        let input = std::fs::read_to_string("tests/input2_input.json").unwrap();
+        let encoded = Base64::encode_string(input.as_bytes());
+        let base = ["juvix-cairo-vm", "tests/input2.json", "--layout", "small"];
+        let args = [&base[..], &["--program_input_base64", &encoded]]
+            .concat()
+            .into_iter()
+            .map(String::from);
+        assert_matches!(run_cli(args), Ok(()));
+
+        let args = [&base[..], &["--program_input_base64", "not base64!"]]
+            .concat()
+            .into_iter()
+            .map(String::from);
+        assert_matches!(run_cli(args), Err(Error::ProgramInputBase64(_)));
+
+        let invalid_utf8 = Base64::encode_string(&[0xff, 0xfe]);
+        let args = [&base[..], &["--program_input_base64", &invalid_utf8]]
+            .concat()
+            .into_iter()
+            .map(String::from);
+        assert_matches!(run_cli(args), Err(Error::ProgramInputBase64(_)));
+
+        let args = [
+            &base[..],
+            &[
+                "--program_input",
+                "tests/input2_input.json",
+                "--program_input_base64",
+                &encoded,
+            ],
+        ]
+        .concat()
+        .into_iter()
+        .map(String::from);
+        assert_matches!(run_cli(args), Err(Error::Cli(_)));
+    }
+
+    #[test]
+    fn test_memory_v2() {
+        let dir = TempDir::new();
+        let binary_path = dir.join("juvix_cairo_vm_test_v2.binary.memory");
+        let v2_path = dir.join("juvix_cairo_vm_test_v2.memory");
+        for (path, format) in [(&binary_path, "binary"), (&v2_path, "v2")] {
+            let args = [
+                "juvix-cairo-vm",
+                "tests/fibonacci.json",
+                "--memory_file",
+                path.to_str().unwrap(),
+                "--memory_format",
+                format,
+            ]
+            .into_iter()
+            .map(String::from);
+            assert_matches!(run_cli(args), Ok(()));
+        }
+        let binary = std::fs::read(&binary_path).unwrap();
+        let v2 = std::fs::read(&v2_path).unwrap();
+
+        assert_eq!(&v2[0..4], b"JCVM");
+        assert_eq!(u32::from_le_bytes(v2[4..8].try_into().unwrap()), 2);
+        let count = u64::from_le_bytes(v2[8..16].try_into().unwrap()) as usize;
+        assert_eq!(u32::from_le_bytes(v2[16..20].try_into().unwrap()), 32);
+        assert!(count > 0);
+        assert_eq!(v2.len(), 20 + count * 40);
+        assert_eq!(&v2[20..], binary);
+    }
+
+    #[test]
+    fn test_handle_interrupt() {
+        use std::sync::atomic::{AtomicBool, Ordering};
+
+        let dir = TempDir::new();
+        let trace_path = dir.join("interrupt.trace");
+        let args = |program: &str| {
+            [
+                "juvix-cairo-vm",
+                program,
+                "--handle_interrupt",
+                "--trace_file",
+                trace_path.to_str().unwrap(),
+            ]
+            .into_iter()
+            .map(String::from)
+            .collect::<Vec<_>>()
+        };
+
+        // a SIGINT received before the run does not stop it
+        interrupt::INTERRUPTED.store(true, Ordering::SeqCst);
+        assert_matches!(run_cli(args("tests/fibonacci.json").into_iter()), Ok(()));
+        std::fs::remove_file(&trace_path).unwrap();
+
+        // the program never ends, so only the SIGINT stops it
+        let done = AtomicBool::new(false);
+        std::thread::scope(|scope| {
+            scope.spawn(|| {
+                while !done.load(Ordering::SeqCst) {
+                    // what the SIGINT handler does
+                    interrupt::INTERRUPTED.store(true, Ordering::SeqCst);
+                    std::thread::sleep(Duration::from_millis(10));
+                }
+            });
+            let result = run_cli(args("tests/infinite_loop.json").into_iter());
+            done.store(true, Ordering::SeqCst);
+            assert_matches!(result, Err(Error::Interrupted));
+        });
+        // no partial trace is left behind
+        assert!(!trace_path.exists());
+    }
+
+    #[test]
+    fn test_benchmark_layouts() {
+        let args = Args::try_parse_from(["juvix-cairo-vm", "tests/fibonacci.json"]).unwrap();
+        let program_content = std::fs::read("tests/fibonacci.json").unwrap();
+        let benchmarks =
+            benchmark_layouts(&program_content, &args, ProgramInput::new(HashMap::new())).unwrap();
+        // the program uses no builtin, so every layout is compatible
+        let layouts: Vec<&str> = benchmarks.iter().map(|row| row.layout).collect();
+        assert_eq!(layouts, LAYOUTS);
+        assert!(benchmarks.iter().all(|row| row.steps > 0));
+        assert_eq!(
+            format_layout_benchmarks(&benchmarks).lines().count(),
+            LAYOUTS.len() + 1
+        );
+    }
+
+    #[test]
+    fn test_benchmark_layouts_builtins() {
+        // proof mode allows missing builtins, which must not make a layout
+        // without ec_op or poseidon count as compatible
+        let args =
+            Args::try_parse_from(["juvix-cairo-vm", "tests/input4.json", "--proof_mode"]).unwrap();
+        let program_content = std::fs::read("tests/input4.json").unwrap();
+        let program_input =
+            ProgramInput::from_json(&std::fs::read_to_string("tests/input4_input.json").unwrap())
+                .unwrap();
+        let benchmarks = benchmark_layouts(&program_content, &args, program_input).unwrap();
+        let layouts: Vec<&str> = benchmarks.iter().map(|row| row.layout).collect();
+        assert_eq!(layouts, ["starknet", "starknet_with_keccak", "all_cairo"]);
+    }
+
+    #[test]
+    fn test_program_info() {
+        let info = program_info(&std::fs::read("tests/fibonacci.json").unwrap()).unwrap();
+        assert_eq!(
+            info,
+            ProgramInfo {
+                builtins: vec![],
+                entrypoints: vec![("main".to_string(), 0), ("fib".to_string(), 11)],
+                data_len: 24,
+            }
+        );
+        assert_eq!(
+            info.to_string(),
+            "builtins: []\nentrypoints:\n  main: 0\n  fib: 11\ndata length: 24\n"
+        );
+        let info = program_info(&std::fs::read("tests/input2.json").unwrap()).unwrap();
+        assert_eq!(info.builtins, ["output"]);
+    }
+
+    #[test]
+    fn test_list_entrypoints() {
+        let entrypoints =
+            list_entrypoints(&std::fs::read("tests/fibonacci.json").unwrap()).unwrap();
+        assert!(entrypoints.contains(&("main".to_string(), 0)));
+        let args = [
+            "juvix-cairo-vm",
+            "tests/fibonacci.json",
+            "--entrypoint_list",
+        ]
+        .into_iter()
+        .map(String::from);
+        assert_matches!(run_cli(args), Ok(()));
+    }
+
+    #[test]
+    fn test_collect_hints() {
+        let hints = collect_hints(&std::fs::read("tests/output_then_fail.json").unwrap()).unwrap();
+        assert_eq!(
+            hints,
+            [
+                Hint::Input("X".into()),
+                Hint::Input("Y".into()),
+                Hint::InputLen("X".into())
+            ]
+        );
+        assert_eq!(format_hint_census(&hints), "Input: 2\nInputLen: 1\n");
+        assert!(
+            collect_hints(&std::fs::read("tests/fibonacci.json").unwrap())
+                .unwrap()
+                .is_empty()
+        );
+        assert_matches!(
+            collect_hints(&std::fs::read("tests/builtin_hint.json").unwrap()),
+            Err(Error::Hint(_))
+        );
+    }
+
+    #[test]
+    fn test_program_input_ns() {
+        let args = Args::try_parse_from([
+            "juvix-cairo-vm",
+            "tests/input2.json",
+            "--program_input",
+            "tests/input2_input.json",
+            "--program_input_ns",
+            "a=tests/input2_input.json",
+            "--program_input_ns",
+            "b.c=tests/input1_input.json",
+        ])
+        .unwrap();
+        let program_input = load_program_input(&args).unwrap();
+        let felt = |var| match program_input.get(var) {
+            program_input::Value::ValueFelt(felt) => *felt,
+            value => panic!("unexpected value {value:?}"),
+        };
+        assert_eq!(felt("X"), Felt252::from(9));
+        assert_eq!(felt("a.X"), Felt252::from(9));
+        assert_eq!(felt("b.c.abba"), Felt252::from(1234));
+
+        let args = Args::try_parse_from([
+            "juvix-cairo-vm",
+            "tests/input2.json",
+            "--program_input_ns",
+            "a=tests/input2_input.json",
+            "--program_input_ns",
+            "a=tests/input2_input.json",
+        ])
+        .unwrap();
+        assert_matches!(
+            load_program_input(&args),
+            Err(Error::DuplicateInputKey(key)) if key.starts_with("a.")
+        );
+        assert!(Args::try_parse_from([
+            "juvix-cairo-vm",
+            "tests/input2.json",
+            "--program_input_ns",
+            "1a=tests/input2_input.json",
+        ])
+        .is_err());
+    }
+
+    #[rstest]
+    #[case("tests/fibonacci.json", &[], false)]
+    // only builtin hints
+    #[case("tests/builtin_hint.json", &["--builtin_hints"], false)]
+    #[case(
+        "tests/input2.json",
+        &["--layout", "small", "--program_input", "tests/input2_input.json"],
+        true
+    )]
+    fn test_require_hints(#[case] program: &str, #[case] extra: &[&str], #[case] has_hints: bool) {
+        let base = ["juvix-cairo-vm", program, "--require_hints"];
+        let args = [&base[..], extra].concat().into_iter().map(String::from);
+        match run_cli(args) {
+            Ok(()) => assert!(has_hints),
+            Err(Error::NoHints) => assert!(!has_hints),
+            Err(err) => panic!("unexpected error: {err}"),
+        }
+    }
+
+    #[rstest]
+    #[case(false)]
+    #[case(true)]
+    fn test_fail_on_warnings(#[case] fail_on_warnings: bool) {
+        let mut args = vec!["juvix-cairo-vm", "tests/alloc_zero.json"];
+        if fail_on_warnings {
+            args.push("--fail_on_warnings");
+        }
+        let result = run_cli(args.into_iter().map(String::from));
+        if fail_on_warnings {
+            assert_matches!(
+                result,
+                Err(Error::Warnings(warnings)) if warnings.len() == 1 && warnings[0].contains("Alloc(0)")
+            );
+        } else {
+            assert_matches!(result, Ok(()));
+        }
+    }
+
+    #[test]
+    fn test_warn_unfilled_output() {
+        let run_with = |program: &str, layout: Option<&str>| {
+            let config = Config {
+                layout: layout.map(String::from),
+                warn_unfilled_output: Some(true),
+                ..Default::default()
+            };
+            run_json(&std::fs::read_to_string(program).unwrap(), "{}", config).unwrap()
+        };
+        let result = run_with("tests/unfilled_output.json", Some("small"));
+        // the output is still shown, with the hole
+        assert_eq!(result.output, "<missing>\n7\n");
+        assert_eq!(result.warnings().len(), 1);
+        assert!(result.warnings()[0].contains("offsets 0 were never written"));
+        let result = run_with("tests/fibonacci.json", None);
+        assert!(result.warnings().is_empty());
+        let args = [
+            "juvix-cairo-vm",
+            "tests/unfilled_output.json",
             "--layout",
             "small",
+            "--warn_unfilled_output",
+        ];
+        assert_matches!(run_cli(args.into_iter().map(String::from)), Ok(()));
+    }
+
+    #[test]
+    fn test_single_entrypoint_without_main() {
+        let args = ["juvix-cairo-vm", "tests/no_main.json", "--fail_on_warnings"];
+        assert_matches!(
+            run_cli(args.into_iter().map(String::from)),
+            Err(Error::Warnings(warnings)) if warnings[0].contains("entrypoint run")
+        );
+        let args = ["juvix-cairo-vm", "tests/no_main.json"];
+        assert_matches!(run_cli(args.into_iter().map(String::from)), Ok(()));
+        let args = [
+            "juvix-cairo-vm",
+            "tests/no_main.json",
+            "--entrypoint",
+            "run",
+        ];
+        assert_matches!(run_cli(args.into_iter().map(String::from)), Ok(()));
+    }
+
+    #[test]
+    fn test_several_entrypoints_without_main() {
+        let args = ["juvix-cairo-vm", "tests/no_main_two_entrypoints.json"];
+        assert_matches!(
+            run_cli(args.into_iter().map(String::from)),
+            Err(Error::AmbiguousEntrypoint(names)) if names == ["first", "second"]
+        );
+    }
+
+    #[test]
+    fn test_unsupported_hints() {
+        assert_eq!(
+            unsupported_hints(&std::fs::read("tests/builtin_hint.json").unwrap()).unwrap(),
+            [
+                (0, "vm_enter_scope()".to_string()),
+                (0, "vm_exit_scope()".to_string())
+            ]
+        );
+        assert!(
+            unsupported_hints(&std::fs::read("tests/output_then_fail.json").unwrap())
+                .unwrap()
+                .is_empty()
+        );
+        let args = [
+            "juvix-cairo-vm",
+            "tests/builtin_hint.json",
+            "--dry_run_hints",
         ]
         .into_iter()
         .map(String::from);
-        assert_matches!(run_cli(args), Err(Error::Runner(_)));
+        assert_matches!(run_cli(args), Err(Error::UnsupportedHints(2)));
+    }
+
+    #[test]
+    fn test_program_hash() {
+        let program_content = std::fs::read("tests/fibonacci.json").unwrap();
+        let hash = program_hash(&program_content).unwrap();
+        assert_eq!(hash.len(), 64);
+        assert_eq!(program_hash(&program_content).unwrap(), hash);
+        // the same program with its keys reordered and reformatted
+        let program: serde_json::Value = serde_json::from_slice(&program_content).unwrap();
+        let serde_json::Value::Object(map) = program else {
+            panic!("the program is not a JSON object");
+        };
+        let reordered: serde_json::Map<_, _> = map.into_iter().rev().collect();
+        let reordered = serde_json::to_string_pretty(&reordered).unwrap();
+        assert_eq!(program_hash(reordered.as_bytes()).unwrap(), hash);
+        assert_ne!(
+            program_hash(&std::fs::read("tests/input2.json").unwrap()).unwrap(),
+            hash
+        );
+        let args = [
+            "juvix-cairo-vm",
+            "tests/fibonacci.json",
+            "--print_program_hash",
+        ]
+        .into_iter()
+        .map(String::from);
+        assert_matches!(run_cli(args), Ok(()));
     }
 
     #[rstest]
-    #[case("tests/input2.json", "tests/input2_input.json", "83\n")]
-    #[case("tests/input3.json", "tests/input3_input.json", "14\n")]
-    #[case("tests/input4.json", "tests/input4_input.json", "4\n16\n9\n")]
-    fn test_input_output_positive(
-        #[case] program: &str,
-        #[case] input: &str,
-        #[case] output: &str,
-    ) {
+    #[case(false)]
+    #[case(true)]
+    fn test_manifest(#[case] hashes: bool) {
+        let dir = TempDir::new();
+        let path = |name: &str| {
+            dir.join(format!("juvix_cairo_vm_test_manifest_{hashes}.{name}"))
+                .to_str()
+                .unwrap()
+                .to_string()
+        };
+        let mut args = vec![
+            "juvix-cairo-vm".to_string(),
+            "tests/proof_programs/fibonacci.json".to_string(),
+            "--proof_mode".to_string(),
+            "--trace_file".to_string(),
+            path("trace"),
+            "--memory_file".to_string(),
+            path("memory"),
+            "--air_public_input".to_string(),
+            path("public.json"),
+            "--air_private_input".to_string(),
+            path("private.json"),
+            "--manifest".to_string(),
+            path("manifest.json"),
+        ];
+        if hashes {
+            args.push("--manifest_hashes".to_string());
+        }
+        assert_matches!(run_cli(args.into_iter()), Ok(()));
+        let manifest: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(path("manifest.json")).unwrap()).unwrap();
+        let program = std::fs::read("tests/proof_programs/fibonacci.json").unwrap();
+        assert_eq!(manifest["program_hash"], program_hash(&program).unwrap());
+        assert_eq!(manifest["proof_mode"], true);
+        assert!(manifest["steps"].as_u64().unwrap() > 0);
+        let artifacts = manifest["artifacts"].as_array().unwrap();
+        let kinds: Vec<&str> = artifacts
+            .iter()
+            .map(|artifact| artifact["kind"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            kinds,
+            ["trace", "memory", "air_public_input", "air_private_input"]
+        );
+        for (artifact, name) in
+            artifacts
+                .iter()
+                .zip(["trace", "memory", "public.json", "private.json"])
+        {
+            let contents = std::fs::read(path(name)).unwrap();
+            assert_eq!(artifact["path"], path(name));
+            assert_eq!(artifact["size"], contents.len());
+            if hashes {
+                assert_eq!(artifact["keccak256"], keccak_hex(&contents));
+            } else {
+                assert!(artifact.get("keccak256").is_none());
+            }
+        }
+    }
+
+    #[test]
+    fn test_program_info_cli() {
+        let args = ["juvix-cairo-vm", "tests/fibonacci.json", "--program_info"]
+            .into_iter()
+            .map(String::from);
+        assert_matches!(run_cli(args), Ok(()));
+    }
+
+    #[test]
+    fn test_repeat() {
         let args_cli = [
             "juvix-cairo-vm",
-            program,
-            "--program_input",
-            input,
+            "tests/input2.json",
             "--proof_mode",
             "--layout",
             "small",
+            "--repeat",
+            "3",
         ]
         .into_iter()
         .map(String::from);
         let program_input =
-            ProgramInput::from_json(std::fs::read_to_string(input).unwrap().as_str()).unwrap();
+            ProgramInput::from_json(&std::fs::read_to_string("tests/input2_input.json").unwrap())
+                .unwrap();
         let args = Args::try_parse_from(args_cli).unwrap();
-        assert_eq!(run(args, program_input).unwrap(), output);
+        let result = run_timed(args, program_input).unwrap();
+        assert_eq!(result.output, "83\n");
+        assert_eq!(result.timings.len(), 3);
+        assert!(format_timings(&result.timings).starts_with("3 runs: min "));
+    }
+
+    #[rstest]
+    #[case("tests/proof_programs/fibonacci.json", &[], 0)]
+    #[case("tests/input2.json", &["--program_input", "tests/input2_input.json"], 1)]
+    fn test_output_len(#[case] program: &str, #[case] extra: &[&str], #[case] len: usize) {
+        let base = [
+            "juvix-cairo-vm",
+            program,
+            "--proof_mode",
+            "--layout",
+            "small",
+        ];
+        let args_cli = [&base[..], extra].concat();
+        let args = Args::try_parse_from(args_cli).unwrap();
+        let program_input = match args.program_input {
+            Some(ref file) => {
+                ProgramInput::from_json(&std::fs::read_to_string(file).unwrap()).unwrap()
+            }
+            None => ProgramInput::new(HashMap::new()),
+        };
+        let result = run_timed(args, program_input).unwrap();
+        assert_eq!(result.output_len(), len);
+        assert_eq!(result.output_is_empty(), len == 0);
+    }
+
+    #[test]
+    fn test_output_with_holes() {
+        let program = std::fs::read_to_string("tests/unfilled_output.json").unwrap();
+        let config = Config {
+            layout: Some("small".to_string()),
+            ..Default::default()
+        };
+        let result = run_json(&program, "{}", config).unwrap();
+        assert_eq!(result.output, "<missing>\n7\n");
+        assert_eq!(result.output_len(), 2);
+        assert!(!result.output_is_empty());
+        assert_eq!(result.output_cells()[1], Some(Felt252::from(7).into()));
+        assert_eq!(result.output_felts(), None);
+        assert_eq!(result.output_felt_strings(), ["<missing>", "7"]);
+    }
+
+    #[test]
+    fn test_repeat_zero() {
+        let args = ["juvix-cairo-vm", "tests/input2.json", "--repeat", "0"]
+            .into_iter()
+            .map(String::from);
+        assert_matches!(run_cli(args), Err(Error::Cli(_)));
     }
 
     #[rstest]
@@ -535,4 +3660,27 @@ mod tests {
         .map(String::from);
         assert_matches!(run_cli(args_cli), Ok(()));
     }
+
+    #[test]
+    fn test_trace_summary() {
+        let args = Args::try_parse_from(["juvix-cairo-vm", "tests/fibonacci.json"]).unwrap();
+        let result = run_timed(args, ProgramInput::new(HashMap::new())).unwrap();
+        assert!(result.trace_summary().is_none());
+
+        let args_cli = [
+            "juvix-cairo-vm",
+            "tests/fibonacci.json",
+            "--print_trace_summary",
+        ];
+        let args = Args::try_parse_from(args_cli).unwrap();
+        let result = run_timed(args, ProgramInput::new(HashMap::new())).unwrap();
+        let summary = result.trace_summary().unwrap();
+        assert!(summary.len > 10);
+        let (min_pc, max_pc) = summary.pc_range;
+        assert!(min_pc <= summary.first.0 && summary.first.0 <= max_pc);
+        assert!(min_pc <= summary.last.0 && summary.last.0 <= max_pc);
+        assert!(summary
+            .to_string()
+            .starts_with(&format!("trace length: {}\n", summary.len)));
+    }
 }
@@ -1,8 +1,26 @@
 use juvix_cairo_vm::*;
 
 fn main() -> Result<(), Error> {
-    match run_cli(std::env::args()) {
+    let args = match parse_args(std::env::args()) {
+        Ok(args) => args,
         Err(Error::Cli(err)) => err.exit(),
+        Err(err) => return Err(err),
+    };
+    let error_format = args.error_format;
+    match run_args(args) {
+        Err(Error::Cli(err)) => err.exit(),
+        Err(err) if error_format == ErrorFormat::Json => {
+            eprintln!("{}", err.to_json());
+            std::process::exit(if matches!(err, Error::Interrupted) {
+                130
+            } else {
+                1
+            })
+        }
+        Err(Error::Interrupted) => {
+            eprintln!("{}", Error::Interrupted);
+            std::process::exit(130)
+        }
         other => other,
     }
 }
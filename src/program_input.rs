@@ -1,45 +1,539 @@
-use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
 
 use cairo_vm::Felt252;
+use clap::ValueEnum;
 use indexmap::IndexMap;
-use serde::de::Error;
+use num_bigint::BigUint;
+use serde::de::{Error, MapAccess, SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer};
 use serde_json::{Result as JsonResult, Value as JsonValue};
 
+use crate::juvix_hint_processor::hint_processor::flatten_value;
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Value {
     ValueFelt(Felt252),
     ValueBool(bool),
     ValueRecord(IndexMap<String, Value>),
     ValueList(Vec<Value>),
+    ValueString(String),
+}
+
+impl Value {
+    // Returns the felts the Input hint writes for this value, in memory
+    // order, without running the VM. The pointers to records, lists and
+    // strings are given as offsets from the start of the returned vector.
+    pub fn flatten(&self) -> Vec<Felt252> {
+        let mut out = Vec::new();
+        flatten_value(self, &mut out);
+        out
+    }
+}
+
+// The field prime, the default modulus the input integers are checked
+// against under strict validation.
+pub fn field_prime() -> BigUint {
+    Felt252::MAX.to_biguint() + 1u32
+}
+
+// Parses a string field element. Besides the decimal and `0x` hexadecimal
+// forms understood by `Felt252`, `0o` (octal) and `0b` (binary) prefixes are
+// accepted.
+fn felt_from_str(s: &str, options: &InputOptions) -> JsonResult<Felt252> {
+    let radix = match s.get(..2) {
+        Some("0b") | Some("0B") => Some(2),
+        Some("0o") | Some("0O") => Some(8),
+        _ => None,
+    };
+    match radix {
+        Some(radix) => {
+            let num = BigUint::parse_bytes(&s.as_bytes()[2..], radix)
+                .ok_or_else(|| Error::custom("invalid field element"))?;
+            felt_in_field(&num, s, options)
+        }
+        None => {
+            check_str_in_field(s, options)?;
+            serde_json::from_value::<Felt252>(JsonValue::String(strip_hex_zeros(s)?))
+                .map_err(|_| Error::custom("invalid field element"))
+        }
+    }
+}
+
+// Removes the leading zeros of a `0x` (or `0X`) hexadecimal string, which
+// fixed-width hex generators emit, so that `"0x00ff"` denotes the same field
+// element as `"0xff"`. Other strings are returned unchanged.
+fn strip_hex_zeros(s: &str) -> JsonResult<String> {
+    let digits = match s.get(..2) {
+        Some("0x") | Some("0X") => &s[2..],
+        _ => return Ok(s.to_string()),
+    };
+    let significant = digits.trim_start_matches('0');
+    // longer digit strings do not fit in 256 bits, and make the `Felt252`
+    // parser panic
+    if significant.len() > 64 {
+        return Err(Error::custom("invalid field element"));
+    }
+    if significant.is_empty() && !digits.is_empty() {
+        return Ok("0x0".to_string());
+    }
+    Ok(format!("0x{significant}"))
 }
 
-fn value_from_json(val: JsonValue) -> JsonResult<Value> {
+// Under strict validation, rejects the integer `num`, written `s` in the
+// input, unless it is below the range modulus, normally the field prime.
+// Every form of integer input is checked here.
+fn check_in_field(num: &BigUint, s: &str, options: &InputOptions) -> JsonResult<()> {
+    if options.validation == FeltInputValidation::Strict && *num >= options.range_modulus {
+        return Err(Error::custom(format!("{s} is out of the field range")));
+    }
+    Ok(())
+}
+
+// Checks `num` with check_in_field and converts it to a field element, which
+// reduces it modulo the field prime under lenient validation.
+fn felt_in_field(num: &BigUint, s: &str, options: &InputOptions) -> JsonResult<Felt252> {
+    check_in_field(num, s, options)?;
+    Ok(Felt252::from(num))
+}
+
+// Applies check_in_field to the absolute value of a decimal or `0x`
+// hexadecimal integer. Strings which are not integers are left for the actual
+// parser to reject.
+fn check_str_in_field(s: &str, options: &InputOptions) -> JsonResult<()> {
+    let unsigned = s.strip_prefix('-').unwrap_or(s);
+    let num = match unsigned.get(..2) {
+        Some("0x") | Some("0X") => BigUint::parse_bytes(&unsigned.as_bytes()[2..], 16),
+        _ => BigUint::parse_bytes(unsigned.as_bytes(), 10),
+    };
+    match num {
+        Some(num) => check_in_field(&num, s, options),
+        None => Ok(()),
+    }
+}
+
+// Parses a JSON number. Under strict validation, only integer literals are
+// accepted. Under lenient validation, so are the numbers in floating-point or
+// scientific notation denoting an integer, e.g. `2.0`, `1e9` or `1.5e1`.
+fn felt_from_number(s: &str, options: &InputOptions) -> JsonResult<Felt252> {
+    let float = s.contains(['.', 'e', 'E']);
+    if options.validation == FeltInputValidation::Strict && float {
+        return Err(Error::custom(format!(
+            "floating-point numbers are not field elements, got {s}"
+        )));
+    }
+    let digits = if float {
+        integer_from_float(s)?
+    } else {
+        s.to_string()
+    };
+    check_str_in_field(&digits, options)?;
+    Felt252::from_dec_str(&digits).map_err(|_| Error::custom("invalid field element"))
+}
+
+// Expands a number in floating-point notation to the decimal digits of the
+// integer it denotes, failing if it is not an integer.
+fn integer_from_float(s: &str) -> JsonResult<String> {
+    let invalid = || Error::custom("invalid field element");
+    let (sign, unsigned) = match s.strip_prefix('-') {
+        Some(rest) => ("-", rest),
+        None => ("", s),
+    };
+    let (mantissa, exponent) = match unsigned.split_once(['e', 'E']) {
+        Some((mantissa, exponent)) => (mantissa, exponent.parse::<i64>().map_err(|_| invalid())?),
+        None => (unsigned, 0),
+    };
+    let (int_part, frac_part) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    let mut digits = format!("{int_part}{frac_part}");
+    // the value is `digits * 10^scale`
+    let scale = exponent - frac_part.len() as i64;
+    if scale >= 0 {
+        // no field element has more than 76 decimal digits
+        if digits.len() as i64 + scale > 80 {
+            return Err(invalid());
+        }
+        digits.push_str(&"0".repeat(scale as usize));
+    } else {
+        let cut = digits.len().saturating_sub(scale.unsigned_abs() as usize);
+        if digits[cut..].bytes().any(|d| d != b'0') {
+            return Err(Error::custom(format!(
+                "field elements must be integers, got {s}"
+            )));
+        }
+        digits.truncate(cut);
+    }
+    if digits.is_empty() {
+        digits.push('0');
+    }
+    Ok(format!("{sign}{digits}"))
+}
+
+// Parses the digits of a field element given with an explicit radix.
+fn felt_from_radix(
+    digits: &JsonValue,
+    radix: &JsonValue,
+    options: &InputOptions,
+) -> JsonResult<Felt252> {
+    let radix = match radix.as_u64() {
+        Some(radix @ (2 | 8 | 10 | 16)) => radix as u32,
+        _ => return Err(Error::custom(format!("unsupported radix {radix}"))),
+    };
+    let digits = digits
+        .as_str()
+        .ok_or_else(|| Error::custom("invalid field element"))?;
+    let num = BigUint::parse_bytes(digits.as_bytes(), radix)
+        .ok_or_else(|| Error::custom("invalid field element"))?;
+    felt_in_field(&num, digits, options)
+}
+
+// Assembles a field element from four little-endian u64 limbs.
+fn felt_from_limbs(limbs: &[JsonValue], options: &InputOptions) -> JsonResult<Felt252> {
+    if limbs.len() != 4 {
+        return Err(Error::custom(format!(
+            "expected 4 limbs, got {}",
+            limbs.len()
+        )));
+    }
+    let mut num = BigUint::default();
+    for limb in limbs.iter().rev() {
+        let limb = limb
+            .as_u64()
+            .ok_or_else(|| Error::custom("limbs must be unsigned 64-bit integers"))?;
+        num = (num << 64u32) + limb;
+    }
+    felt_in_field(&num, &num.to_string(), options)
+}
+
+// Computes the field element `numerator / denominator` from the two parts of
+// a `frac` input, each a JSON number or a decimal, `0x`, `0o` or `0b` string.
+fn felt_from_frac(parts: &[JsonValue], options: &InputOptions) -> JsonResult<Felt252> {
+    let part = |part: &JsonValue| match part {
+        JsonValue::Number(num) => felt_from_number(num.as_str(), options),
+        JsonValue::String(s) => {
+            let digits = s.strip_prefix('-').unwrap_or(s);
+            if !digits.is_empty() && digits.bytes().all(|d| d.is_ascii_digit()) {
+                felt_from_number(s, options)
+            } else {
+                felt_from_str(s, options)
+            }
+        }
+        _ => Err(Error::custom("invalid field element")),
+    };
+    let [numerator, denominator] = parts else {
+        return Err(Error::custom(format!(
+            "expected a numerator and a denominator, got {} values",
+            parts.len()
+        )));
+    };
+    let inverse = part(denominator)?
+        .inverse()
+        .ok_or_else(|| Error::custom("the denominator of a fraction must not be zero"))?;
+    Ok(part(numerator)? * inverse)
+}
+
+// Replaces a string of the form `${VAR}` with the value of the environment
+// variable `VAR`. Other strings are returned unchanged.
+fn resolve_env_var(s: String) -> JsonResult<String> {
+    match s.strip_prefix("${").and_then(|var| var.strip_suffix('}')) {
+        Some(var) => std::env::var(var)
+            .map_err(|_| Error::custom(format!("environment variable {var} is not set"))),
+        None => Ok(s),
+    }
+}
+
+fn value_from_json(val: JsonValue, options: &InputOptions) -> JsonResult<Value> {
     match val {
-        JsonValue::Number(num) => Felt252::from_dec_str(num.as_str())
-            .map_err(|_| Error::custom("invalid field element"))
-            .map(|x| Value::ValueFelt(x)),
-        JsonValue::String(_) => serde_json::from_value::<Felt252>(val)
-            .map_err(|_| Error::custom("invalid field element"))
-            .map(|x| Value::ValueFelt(x)),
+        JsonValue::Number(num) => felt_from_number(num.as_str(), options).map(Value::ValueFelt),
+        JsonValue::String(s) if options.resolve_env => {
+            felt_from_str(&resolve_env_var(s)?, options).map(Value::ValueFelt)
+        }
+        JsonValue::String(s) => felt_from_str(&s, options).map(Value::ValueFelt),
         JsonValue::Bool(_) => serde_json::from_value::<bool>(val)
             .map_err(|_| Error::custom("invalid boolean"))
             .map(|x| Value::ValueBool(x)),
+        // A string is given as an object with a single `str` field
+        JsonValue::Object(obj) if obj.len() == 1 && obj.contains_key("str") => {
+            match obj.into_iter().next() {
+                Some((_, JsonValue::String(s))) => Ok(Value::ValueString(s)),
+                _ => Err(Error::custom("invalid string")),
+            }
+        }
+        // A field element may be given as its digits in an explicit radix
+        JsonValue::Object(obj)
+            if obj.len() == 2 && obj.contains_key("felt") && obj.contains_key("radix") =>
+        {
+            felt_from_radix(&obj["felt"], &obj["radix"], options).map(Value::ValueFelt)
+        }
+        // A field element may be given as an object with a single `limbs`
+        // field holding four little-endian u64 limbs
+        JsonValue::Object(obj) if obj.len() == 1 && obj.contains_key("limbs") => {
+            match obj.into_iter().next() {
+                Some((_, JsonValue::Array(limbs))) => {
+                    felt_from_limbs(&limbs, options).map(Value::ValueFelt)
+                }
+                _ => Err(Error::custom("invalid limbs")),
+            }
+        }
+        // A field element may be given as an object with a single `frac` field
+        // holding a numerator and a denominator
+        JsonValue::Object(obj) if obj.len() == 1 && obj.contains_key("frac") => {
+            match obj.into_iter().next() {
+                Some((_, JsonValue::Array(parts))) => {
+                    felt_from_frac(&parts, options).map(Value::ValueFelt)
+                }
+                _ => Err(Error::custom("invalid fraction")),
+            }
+        }
         JsonValue::Object(obj) => {
             let mres: JsonResult<IndexMap<String, Value>> = obj
                 .into_iter()
-                .map(|(k, v)| value_from_json(v).map(|x| (k, x)))
+                .map(|(k, v)| value_from_json(v, options).map(|x| (k, x)))
                 .collect();
             Ok(Value::ValueRecord(mres?))
         }
         JsonValue::Array(arr) => {
-            let mres: JsonResult<Vec<Value>> =
-                arr.into_iter().map(|x| value_from_json(x)).collect();
+            let mres: JsonResult<Vec<Value>> = arr
+                .into_iter()
+                .map(|x| value_from_json(x, options))
+                .collect();
             Ok(Value::ValueList(mres?))
         }
         _ => Err(Error::custom("invalid value")),
     }
 }
 
+// Parses an input file into the JSON it stands for, before any of the
+// interpretation done by `value_from_json`.
+fn json_from_str_with_format(input: &str, format: InputFormat) -> JsonResult<JsonValue> {
+    match format {
+        InputFormat::Json => serde_json::from_str(input),
+        InputFormat::Jsonc => serde_json::from_str(&strip_jsonc(input)),
+        InputFormat::Toml => toml_to_json(input),
+    }
+}
+
+// Validates the raw input against the JSON Schema `schema`. Returns the
+// violations, each prefixed with the path of the offending value.
+pub fn validate_schema(
+    input: &str,
+    format: InputFormat,
+    schema: &JsonValue,
+) -> Result<(), Vec<String>> {
+    let validator =
+        jsonschema::validator_for(schema).map_err(|e| vec![format!("invalid schema: {e}")])?;
+    let input = json_from_str_with_format(input, format).map_err(|e| vec![e.to_string()])?;
+    let errors: Vec<String> = validator
+        .iter_errors(&input)
+        .map(|e| format!("{}: {}", e.instance_path, e))
+        .collect();
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+// Deserializes any JSON value to nothing, failing on an object with a
+// repeated key. `serde_json::Value` silently keeps the last of them.
+struct NoDuplicateKeys;
+
+impl<'de> Deserialize<'de> for NoDuplicateKeys {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(NoDuplicateKeys)
+    }
+}
+
+impl<'de> Visitor<'de> for NoDuplicateKeys {
+    type Value = NoDuplicateKeys;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a JSON value")
+    }
+
+    fn visit_bool<E: Error>(self, _: bool) -> Result<Self::Value, E> {
+        Ok(NoDuplicateKeys)
+    }
+
+    fn visit_i64<E: Error>(self, _: i64) -> Result<Self::Value, E> {
+        Ok(NoDuplicateKeys)
+    }
+
+    fn visit_u64<E: Error>(self, _: u64) -> Result<Self::Value, E> {
+        Ok(NoDuplicateKeys)
+    }
+
+    fn visit_f64<E: Error>(self, _: f64) -> Result<Self::Value, E> {
+        Ok(NoDuplicateKeys)
+    }
+
+    fn visit_str<E: Error>(self, _: &str) -> Result<Self::Value, E> {
+        Ok(NoDuplicateKeys)
+    }
+
+    fn visit_unit<E: Error>(self) -> Result<Self::Value, E> {
+        Ok(NoDuplicateKeys)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        while seq.next_element::<NoDuplicateKeys>()?.is_some() {}
+        Ok(NoDuplicateKeys)
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut keys = HashSet::new();
+        while let Some(key) = map.next_key::<String>()? {
+            map.next_value::<NoDuplicateKeys>()?;
+            if !keys.insert(key.clone()) {
+                return Err(A::Error::custom(format!("duplicate key {key}")));
+            }
+        }
+        Ok(NoDuplicateKeys)
+    }
+}
+
+/// How strictly the field elements of the program input are checked.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FeltInputValidation {
+    /// Reject numbers in floating-point or scientific notation, integers
+    /// outside the field and duplicate keys
+    #[default]
+    Strict,
+    /// Accept floats denoting integers, reduce integers modulo the field
+    /// prime and keep the last of duplicate keys
+    Lenient,
+}
+
+/// How a program input file is read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InputOptions {
+    pub format: InputFormat,
+    // Read the field elements given as `"${VAR}"` from the environment
+    // variable `VAR`
+    pub resolve_env: bool,
+    pub validation: FeltInputValidation,
+    // The bound of the input integers under strict validation, normally the
+    // field prime. A smaller one is for experimenting with smaller fields: the
+    // values are still felts, and the VM arithmetic is unchanged.
+    pub range_modulus: BigUint,
+}
+
+impl Default for InputOptions {
+    fn default() -> Self {
+        InputOptions {
+            format: InputFormat::default(),
+            resolve_env: false,
+            validation: FeltInputValidation::default(),
+            range_modulus: field_prime(),
+        }
+    }
+}
+
+/// The syntax of a program input file.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InputFormat {
+    /// Strict JSON
+    #[default]
+    Json,
+    /// JSON with `//` and `/* */` comments and trailing commas
+    Jsonc,
+    /// TOML
+    Toml,
+}
+
+// Removes comments and trailing commas, turning the relaxed JSON accepted in
+// the `jsonc` input format into strict JSON. Numbers are left untouched so
+// that large field elements keep their full precision.
+fn strip_jsonc(input: &str) -> String {
+    let mut stripped = String::with_capacity(input.len());
+    let mut chars = input.chars().peekable();
+    let mut in_string = false;
+    while let Some(c) = chars.next() {
+        if in_string {
+            stripped.push(c);
+            match c {
+                '\\' => stripped.extend(chars.next()),
+                '"' => in_string = false,
+                _ => (),
+            }
+            continue;
+        }
+        match (c, chars.peek()) {
+            ('"', _) => {
+                in_string = true;
+                stripped.push(c);
+            }
+            ('/', Some('/')) => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        stripped.push(c);
+                        break;
+                    }
+                }
+            }
+            ('/', Some('*')) => {
+                chars.next();
+                let mut prev = ' ';
+                for c in chars.by_ref() {
+                    if prev == '*' && c == '/' {
+                        break;
+                    }
+                    prev = c;
+                }
+                stripped.push(' ');
+            }
+            _ => stripped.push(c),
+        }
+    }
+
+    let mut output = String::with_capacity(stripped.len());
+    let mut in_string = false;
+    let mut escaped = false;
+    for (i, c) in stripped.char_indices() {
+        if in_string {
+            in_string = escaped || c != '"';
+            escaped = !escaped && c == '\\';
+        } else if c == '"' {
+            in_string = true;
+        } else if c == ',' {
+            let next = stripped[i + 1..].trim_start().chars().next();
+            if matches!(next, Some(']') | Some('}')) {
+                continue;
+            }
+        }
+        output.push(c);
+    }
+    output
+}
+
+// Converts a TOML value into the equivalent JSON value. Floats and dates have
+// no counterpart in program input and are rejected.
+fn json_from_toml(val: toml::Value) -> JsonResult<JsonValue> {
+    match val {
+        toml::Value::String(s) => Ok(JsonValue::String(s)),
+        toml::Value::Integer(i) => Ok(JsonValue::from(i)),
+        toml::Value::Boolean(b) => Ok(JsonValue::Bool(b)),
+        toml::Value::Array(arr) => arr
+            .into_iter()
+            .map(json_from_toml)
+            .collect::<JsonResult<Vec<JsonValue>>>()
+            .map(JsonValue::Array),
+        toml::Value::Table(table) => table
+            .into_iter()
+            .map(|(k, v)| json_from_toml(v).map(|x| (k, x)))
+            .collect::<JsonResult<serde_json::Map<String, JsonValue>>>()
+            .map(JsonValue::Object),
+        toml::Value::Float(_) | toml::Value::Datetime(_) => Err(Error::custom("invalid value")),
+    }
+}
+
+fn toml_to_json(input: &str) -> JsonResult<JsonValue> {
+    let table = toml::from_str::<toml::Table>(input).map_err(Error::custom)?;
+    json_from_toml(toml::Value::Table(table))
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ProgramInput {
     input_values: HashMap<String, Value>,
@@ -51,11 +545,21 @@ impl ProgramInput {
     }
 
     pub fn from_json(input: &str) -> JsonResult<Self> {
-        match serde_json::from_str(input)? {
+        Self::from_str_with_format(input, InputFormat::Json)
+    }
+
+    // Interprets JSON the caller already parsed, like from_json does with the
+    // JSON it parses. Duplicate keys cannot be detected in a parsed value.
+    pub fn from_value(input: JsonValue) -> JsonResult<Self> {
+        Self::from_json_value(input, &InputOptions::default())
+    }
+
+    fn from_json_value(input: JsonValue, options: &InputOptions) -> JsonResult<Self> {
+        match input {
             JsonValue::Object(obj) => {
                 let mut res = HashMap::new();
                 for (k, v) in obj {
-                    res.insert(k, value_from_json(v)?);
+                    res.insert(k, value_from_json(v, options)?);
                 }
                 Ok(ProgramInput::new(res))
             }
@@ -63,9 +567,88 @@ impl ProgramInput {
         }
     }
 
+    pub fn from_jsonc(input: &str) -> JsonResult<Self> {
+        Self::from_str_with_format(input, InputFormat::Jsonc)
+    }
+
+    pub fn from_toml(input: &str) -> JsonResult<Self> {
+        Self::from_str_with_format(input, InputFormat::Toml)
+    }
+
+    pub fn from_str_with_format(input: &str, format: InputFormat) -> JsonResult<Self> {
+        Self::from_str_with_options(
+            input,
+            &InputOptions {
+                format,
+                ..Default::default()
+            },
+        )
+    }
+
+    // Like from_str_with_format, but the field elements given as `"${VAR}"`
+    // are read from the environment variable `VAR`.
+    pub fn from_str_resolving_env(input: &str, format: InputFormat) -> JsonResult<Self> {
+        Self::from_str_with_options(
+            input,
+            &InputOptions {
+                format,
+                resolve_env: true,
+                ..Default::default()
+            },
+        )
+    }
+
+    pub fn from_str_with_options(input: &str, options: &InputOptions) -> JsonResult<Self> {
+        // TOML rejects duplicate keys itself
+        if options.validation == FeltInputValidation::Strict {
+            match options.format {
+                InputFormat::Json => serde_json::from_str::<NoDuplicateKeys>(input).map(|_| ())?,
+                InputFormat::Jsonc => {
+                    serde_json::from_str::<NoDuplicateKeys>(&strip_jsonc(input)).map(|_| ())?
+                }
+                InputFormat::Toml => (),
+            }
+        }
+        Self::from_json_value(json_from_str_with_format(input, options.format)?, options)
+    }
+
     pub fn get(&self, var: &str) -> &Value {
         &self.input_values[var]
     }
+
+    pub fn try_get(&self, var: &str) -> Option<&Value> {
+        self.input_values.get(var)
+    }
+
+    // Adds the variables of `other` named `prefix.name`. Fails with the first
+    // name which is already taken.
+    pub fn insert_namespace(&mut self, prefix: &str, other: ProgramInput) -> Result<(), String> {
+        for (name, value) in other.input_values {
+            match self.input_values.entry(format!("{prefix}.{name}")) {
+                Entry::Occupied(entry) => return Err(entry.key().clone()),
+                Entry::Vacant(entry) => entry.insert(value),
+            };
+        }
+        Ok(())
+    }
+
+    pub fn contains_key(&self, var: &str) -> bool {
+        self.input_values.contains_key(var)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &Value)> {
+        self.input_values.iter()
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &String> {
+        self.input_values.keys()
+    }
+
+    // Returns the flattened encoding of the input variable `var` (see
+    // `Value::flatten`), or None if there is no such variable.
+    pub fn flatten_var(&self, var: &str) -> Option<Vec<Felt252>> {
+        self.input_values.get(var).map(Value::flatten)
+    }
 }
 
 #[cfg(test)]
@@ -84,6 +667,16 @@ mod tests {
             (String::from("X"), Value::ValueFelt(Felt252::from(0xAFF)))
         ]))
     ))]
+    #[case((r#"{"X": "0b1010"}"#,
+        ProgramInput::new(HashMap::from([
+            (String::from("X"), Value::ValueFelt(Felt252::from(0b1010)))
+        ]))
+    ))]
+    #[case((r#"{"X": "0o17"}"#,
+        ProgramInput::new(HashMap::from([
+            (String::from("X"), Value::ValueFelt(Felt252::from(0o17)))
+        ]))
+    ))]
     #[case((r#"{"X": true}"#,
         ProgramInput::new(HashMap::from([
             (String::from("X"), Value::ValueBool(true))
@@ -133,7 +726,353 @@ mod tests {
             )
         ]))
     ))]
+    #[case((r#"{"X": {"str": "hello"}}"#,
+        ProgramInput::new(HashMap::from([
+            (String::from("X"), Value::ValueString(String::from("hello")))
+        ]))
+    ))]
+    #[case((r#"{"X": [{"str": ""}, {"str": "a"}]}"#,
+        ProgramInput::new(HashMap::from([
+            (String::from("X"),
+                Value::ValueList(Vec::from([
+                    Value::ValueString(String::new()),
+                    Value::ValueString(String::from("a"))
+                ]))
+            )
+        ]))
+    ))]
     fn tests_program_input_from_json(#[case] arg: (&str, ProgramInput)) {
         assert_eq!(ProgramInput::from_json(arg.0).unwrap(), arg.1)
     }
+
+    #[rstest]
+    #[case("0x00ff", "0xff")]
+    #[case(
+        "0x0000000000000000000000000000000000000000000000000000000000000000ff",
+        "0xff"
+    )]
+    #[case("0X00FF", "0xff")]
+    #[case("0x0", "0x00")]
+    #[case("0x000", "0x0")]
+    #[case("0b0011", "0b11")]
+    #[case("0o007", "0o7")]
+    fn tests_program_input_zero_padded(#[case] padded: &str, #[case] plain: &str) {
+        let parse = |s: &str| ProgramInput::from_json(&format!(r#"{{"X": "{s}"}}"#)).unwrap();
+        assert_eq!(parse(padded), parse(plain));
+    }
+
+    #[test]
+    fn tests_program_input_from_jsonc() {
+        let input = std::fs::read_to_string("tests/input4_input.jsonc").unwrap();
+        let expected = std::fs::read_to_string("tests/input4_input.json").unwrap();
+        assert!(ProgramInput::from_json(&input).is_err());
+        assert_eq!(
+            ProgramInput::from_str_with_format(&input, InputFormat::Jsonc).unwrap(),
+            ProgramInput::from_json(&expected).unwrap()
+        );
+    }
+
+    #[rstest]
+    #[case(
+        r#"{"X": "a // b", "Y": "/* c */",}"#,
+        r#"{"X": "a // b", "Y": "/* c */"}"#
+    )]
+    #[case(
+        r#"{"X": "\\", /* , */ "Y": [1, 2, ], }"#,
+        r#"{"X": "\\",   "Y": [1, 2 ] }"#
+    )]
+    fn tests_strip_jsonc(#[case] input: &str, #[case] expected: &str) {
+        assert_eq!(strip_jsonc(input), expected)
+    }
+
+    #[test]
+    fn tests_program_input_contains_key() {
+        let input = ProgramInput::from_json(r#"{"X": 1}"#).unwrap();
+        assert!(input.contains_key("X"));
+        assert!(!input.contains_key("Y"));
+    }
+
+    #[test]
+    fn tests_program_input_iter() {
+        let input = ProgramInput::from_json(r#"{"X": 1, "Y": true, "Z": [2]}"#).unwrap();
+        let mut keys: Vec<&String> = input.keys().collect();
+        keys.sort();
+        assert_eq!(keys, ["X", "Y", "Z"]);
+        let mut entries: Vec<(&String, &Value)> = input.iter().collect();
+        entries.sort_by_key(|(k, _)| *k);
+        assert_eq!(
+            entries,
+            [
+                (&String::from("X"), &Value::ValueFelt(Felt252::from(1))),
+                (&String::from("Y"), &Value::ValueBool(true)),
+                (
+                    &String::from("Z"),
+                    &Value::ValueList(Vec::from([Value::ValueFelt(Felt252::from(2))]))
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn tests_program_input_from_value() {
+        let value = serde_json::json!({
+            "X": 1,
+            "Y": ["0x2", {"str": "abc"}],
+            "Z": {"a": true}
+        });
+        assert_eq!(
+            ProgramInput::from_value(value.clone()).unwrap(),
+            ProgramInput::from_json(&value.to_string()).unwrap()
+        );
+        assert!(ProgramInput::from_value(serde_json::json!([1, 2])).is_err());
+    }
+
+    fn lenient(input: &str) -> JsonResult<ProgramInput> {
+        ProgramInput::from_str_with_options(
+            input,
+            &InputOptions {
+                validation: FeltInputValidation::Lenient,
+                ..Default::default()
+            },
+        )
+    }
+
+    #[rstest]
+    #[case(r#"{"X": 2.0}"#, Felt252::from(2))]
+    #[case(r#"{"X": -3.00}"#, Felt252::from(-3))]
+    #[case(r#"{"X": 0.0}"#, Felt252::ZERO)]
+    #[case(
+        r#"{"X": 123456789012345678901234567890.0}"#,
+        Felt252::from_dec_str("123456789012345678901234567890").unwrap()
+    )]
+    fn tests_program_input_integral_float(#[case] input: &str, #[case] expected: Felt252) {
+        assert_eq!(
+            lenient(input).unwrap().get("X"),
+            &Value::ValueFelt(expected)
+        );
+        assert!(ProgramInput::from_json(input).is_err());
+    }
+
+    #[rstest]
+    #[case(r#"{"X": 100}"#)]
+    #[case(r#"{"X": "0x64"}"#)]
+    #[case(r#"{"X": [-100]}"#)]
+    #[case(r#"{"X": {"frac": [100, 3]}}"#)]
+    #[case(r#"{"X": "0b1100100"}"#)]
+    #[case(r#"{"X": "0o144"}"#)]
+    #[case(r#"{"X": {"felt": "100", "radix": 10}}"#)]
+    #[case(r#"{"X": {"felt": "64", "radix": 16}}"#)]
+    #[case(r#"{"X": {"limbs": [100, 0, 0, 0]}}"#)]
+    fn tests_program_input_range_modulus(#[case] input: &str) {
+        let with_modulus = |modulus: u32, validation| {
+            ProgramInput::from_str_with_options(
+                input,
+                &InputOptions {
+                    validation,
+                    range_modulus: BigUint::from(modulus),
+                    ..Default::default()
+                },
+            )
+        };
+        assert!(ProgramInput::from_json(input).is_ok());
+        assert!(with_modulus(101, FeltInputValidation::Strict).is_ok());
+        let err = with_modulus(97, FeltInputValidation::Strict).unwrap_err();
+        assert!(err.to_string().contains("out of the field range"));
+        // only strict validation checks the range
+        assert!(with_modulus(97, FeltInputValidation::Lenient).is_ok());
+    }
+
+    #[rstest]
+    #[case(r#"{"X": 1e9}"#, Felt252::from(1_000_000_000))]
+    #[case(r#"{"X": 2e0}"#, Felt252::from(2))]
+    #[case(r#"{"X": 1.5e1}"#, Felt252::from(15))]
+    #[case(r#"{"X": -2E1}"#, Felt252::from(-20))]
+    #[case(r#"{"X": 1e20}"#, Felt252::from(100_000_000_000_000_000_000u128))]
+    fn tests_program_input_scientific(#[case] input: &str, #[case] expected: Felt252) {
+        let err = ProgramInput::from_json(input).unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("floating-point numbers are not field elements, got "));
+        assert_eq!(
+            lenient(input).unwrap().get("X"),
+            &Value::ValueFelt(expected)
+        );
+    }
+
+    #[rstest]
+    #[case(r#"{"X": 1.5e0}"#)]
+    #[case(r#"{"X": 15e-1}"#)]
+    #[case(r#"{"X": 4e80}"#)]
+    fn tests_program_input_scientific_rejected(#[case] input: &str) {
+        assert!(ProgramInput::from_json(input).is_err());
+        assert!(lenient(input).is_err());
+    }
+
+    #[rstest]
+    #[case(r#"{"X": 1.5}"#)]
+    #[case(r#"{"X": 1e-3}"#)]
+    #[case(r#"{"X": [0.25]}"#)]
+    fn tests_program_input_fractional_float(#[case] input: &str) {
+        let err = lenient(input).unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("field elements must be integers, got "));
+    }
+
+    #[test]
+    fn tests_program_input_resolve_env() {
+        std::env::set_var("JUVIX_CAIRO_VM_TEST_SECRET", "0x2a");
+        let input =
+            r#"{"X": "${JUVIX_CAIRO_VM_TEST_SECRET}", "Y": ["${JUVIX_CAIRO_VM_TEST_SECRET}"]}"#;
+        let program_input = ProgramInput::from_str_resolving_env(input, InputFormat::Json).unwrap();
+        assert_eq!(program_input.get("X"), &Value::ValueFelt(Felt252::from(42)));
+        assert_eq!(
+            program_input.get("Y"),
+            &Value::ValueList(vec![Value::ValueFelt(Felt252::from(42))])
+        );
+        // without resolution the string is not a field element
+        assert!(ProgramInput::from_json(input).is_err());
+    }
+
+    #[test]
+    fn tests_program_input_resolve_env_unset() {
+        let err = ProgramInput::from_str_resolving_env(
+            r#"{"X": "${JUVIX_CAIRO_VM_TEST_UNSET}"}"#,
+            InputFormat::Json,
+        )
+        .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("environment variable JUVIX_CAIRO_VM_TEST_UNSET is not set"));
+    }
+
+    #[test]
+    fn tests_program_input_limbs() {
+        let felt =
+            Felt252::from_hex("0x7a3f1e2d4c5b6a798877665544332211ffeeddccbbaa9988776655443322110")
+                .unwrap();
+        let limbs = felt.to_le_digits();
+        let input = format!(
+            r#"{{"X": {{"limbs": [{}, {}, {}, {}]}}}}"#,
+            limbs[0], limbs[1], limbs[2], limbs[3]
+        );
+        let program_input = ProgramInput::from_json(&input).unwrap();
+        assert_eq!(program_input.get("X"), &Value::ValueFelt(felt));
+    }
+
+    #[rstest]
+    #[case(r#"{"X": {"frac": ["3", "4"]}}"#, 3, 4)]
+    #[case(r#"{"X": {"frac": [1, 3]}}"#, 1, 3)]
+    #[case(r#"{"X": {"frac": ["0x10", -7]}}"#, 16, -7)]
+    #[case(r#"{"X": {"frac": [0, "5"]}}"#, 0, 5)]
+    fn tests_program_input_frac(
+        #[case] input: &str,
+        #[case] numerator: i64,
+        #[case] denominator: i64,
+    ) {
+        let program_input = ProgramInput::from_json(input).unwrap();
+        let Value::ValueFelt(felt) = program_input.get("X") else {
+            panic!("not a field element");
+        };
+        assert_eq!(*felt * Felt252::from(denominator), Felt252::from(numerator));
+    }
+
+    #[rstest]
+    #[case(r#"{"X": {"frac": ["3", "0"]}}"#)]
+    #[case(r#"{"X": {"frac": ["3"]}}"#)]
+    #[case(r#"{"X": {"frac": ["3", "4", "5"]}}"#)]
+    #[case(r#"{"X": {"frac": ["3", "four"]}}"#)]
+    #[case(r#"{"X": {"frac": "3/4"}}"#)]
+    fn tests_program_input_frac_invalid(#[case] input: &str) {
+        assert!(ProgramInput::from_json(input).is_err());
+    }
+
+    #[rstest]
+    #[case(r#"{"X": {"felt": "101", "radix": 2}}"#, 5)]
+    #[case(r#"{"X": {"felt": "17", "radix": 8}}"#, 15)]
+    #[case(r#"{"X": {"felt": "255", "radix": 10}}"#, 255)]
+    #[case(r#"{"X": {"felt": "ff", "radix": 16}}"#, 255)]
+    #[case(r#"{"X": {"radix": 16, "felt": "FF"}}"#, 255)]
+    fn tests_program_input_radix(#[case] input: &str, #[case] expected: u64) {
+        let program_input = ProgramInput::from_json(input).unwrap();
+        assert_eq!(
+            program_input.get("X"),
+            &Value::ValueFelt(Felt252::from(expected))
+        );
+    }
+
+    #[rstest]
+    #[case(r#"{"X": 5}"#, &[5])]
+    #[case(r#"{"X": true}"#, &[0])]
+    // fields a and c inline, b points to the list at offset 3 with a single
+    // cons cell (cid 3, value, next) followed by nil (cid 1)
+    #[case(r#"{"X": {"a": 1, "b": [true], "c": 2}}"#, &[1, 3, 2, 3, 0, 6, 1])]
+    // the second element points to the record at offset 6
+    #[case(r#"{"X": [1, {"x": 5}]}"#, &[3, 1, 3, 3, 6, 7, 5, 1])]
+    #[case(r#"{"X": {"str": "ab"}}"#, &[2, 0x6162])]
+    fn tests_program_input_flatten(#[case] input: &str, #[case] expected: &[u64]) {
+        let program_input = ProgramInput::from_json(input).unwrap();
+        let expected: Vec<Felt252> = expected.iter().map(|&x| Felt252::from(x)).collect();
+        assert_eq!(program_input.flatten_var("X").unwrap(), expected);
+        assert_eq!(program_input.get("X").flatten(), expected);
+        assert_eq!(program_input.flatten_var("Y"), None);
+    }
+
+    #[rstest]
+    #[case(r#"{"X": "0b102"}"#)]
+    #[case(r#"{"X": "0o18"}"#)]
+    #[case(r#"{"X": "0b"}"#)]
+    #[case(r#"{"X": {"str": 1}}"#)]
+    #[case(r#"{"X": {"limbs": [1, 2, 3]}}"#)]
+    #[case(r#"{"X": {"limbs": [1, 2, 3, -4]}}"#)]
+    #[case(r#"{"X": {"limbs": [1, 2, 3, 18446744073709551616]}}"#)]
+    #[case(r#"{"X": {"limbs": [0, 0, 0, 18446744073709551615]}}"#)]
+    #[case(r#"{"X": {"limbs": 1}}"#)]
+    #[case(r#"{"X": {"felt": "ff", "radix": 3}}"#)]
+    #[case(r#"{"X": {"felt": "12", "radix": 2}}"#)]
+    #[case(r#"{"X": {"felt": 10, "radix": 10}}"#)]
+    #[case(r#"{"X": {"felt": "800000000000011000000000000000000000000000000000000000000000001", "radix": 16}}"#)]
+    #[case(r#"{"X": "0o100000000000000000000000000000000000000000000000000000000000000000000000000000000000000"}"#)]
+    #[case(r#"{"X": "0x10000000000000000000000000000000000000000000000000000000000000000"}"#)]
+    #[case(r#"{"X": "0x"}"#)]
+    fn tests_program_input_from_json_negative(#[case] arg: &str) {
+        assert!(ProgramInput::from_json(arg).is_err())
+    }
+
+    #[rstest]
+    #[case(r#"{"X": 2.0}"#, Some(Felt252::from(2)))]
+    #[case(r#"{"X": 2.5}"#, None)]
+    // P and P + 1
+    #[case(
+        r#"{"X": 3618502788666131213697322783095070105623107215331596699973092056135872020481}"#,
+        Some(Felt252::ZERO)
+    )]
+    #[case(
+        r#"{"X": "0x800000000000011000000000000000000000000000000000000000000000002"}"#,
+        Some(Felt252::ONE)
+    )]
+    #[case(r#"{"X": 1, "X": 2}"#, Some(Felt252::from(2)))]
+    #[case(r#"{"Y": {"a": 1, "a": 2}, "X": 3}"#, Some(Felt252::from(3)))]
+    fn tests_program_input_felt_validation(
+        #[case] input: &str,
+        #[case] lenient_x: Option<Felt252>,
+    ) {
+        assert!(ProgramInput::from_json(input).is_err());
+        assert_eq!(
+            lenient(input).ok().map(|input| input.get("X").clone()),
+            lenient_x.map(Value::ValueFelt)
+        );
+    }
+
+    #[rstest]
+    #[case(r#"{"X": 2, "Y": [-3, "0x10"], "Z": {"a": 1}}"#)]
+    #[case(
+        r#"{"X": -3618502788666131213697322783095070105623107215331596699973092056135872020480}"#
+    )]
+    fn tests_program_input_strict_accepts(#[case] input: &str) {
+        assert_eq!(
+            ProgramInput::from_json(input).unwrap(),
+            lenient(input).unwrap()
+        );
+    }
 }
@@ -0,0 +1,65 @@
+use std::io::{self, IsTerminal, Write};
+use std::time::{Duration, Instant};
+
+const SPINNER: [char; 4] = ['|', '/', '-', '\\'];
+
+// The indicator is redrawn at most this often, and the clock is only read
+// every `CHECK_STEPS` steps, so that it does not slow the run down. A power
+// of two, so that the check is a mask.
+const REDRAW_INTERVAL: Duration = Duration::from_millis(100);
+const CHECK_STEPS: usize = 1024;
+
+// The --progress indicator: a line on stderr showing the steps executed so
+// far, out of the step limit when there is one, or with a spinner otherwise.
+pub(crate) struct Progress {
+    frame: usize,
+    last_draw: Option<Instant>,
+}
+
+impl Progress {
+    // Returns None when stderr is not a terminal, where the redrawn line
+    // would only clutter the logs.
+    pub(crate) fn new() -> Option<Self> {
+        io::stderr().is_terminal().then_some(Progress {
+            frame: 0,
+            last_draw: None,
+        })
+    }
+
+    // Called on each step with the steps executed so far and the steps left
+    // under the limit, if any.
+    pub(crate) fn update(&mut self, steps: usize, remaining: Option<usize>) {
+        if steps & (CHECK_STEPS - 1) != 0
+            || self
+                .last_draw
+                .is_some_and(|time| time.elapsed() < REDRAW_INTERVAL)
+        {
+            return;
+        }
+        let line = match remaining {
+            Some(remaining) => {
+                let limit = steps.saturating_add(remaining);
+                format!("{steps}/{limit} steps ({}%)", steps * 100 / limit.max(1))
+            }
+            None => {
+                self.frame = (self.frame + 1) % SPINNER.len();
+                format!("{} {steps} steps", SPINNER[self.frame])
+            }
+        };
+        // \x1b[2K clears the previous, possibly longer, line
+        eprint!("\r\x1b[2K{line}");
+        let _ = io::stderr().flush();
+        self.last_draw = Some(Instant::now());
+    }
+}
+
+impl Drop for Progress {
+    // Removes the indicator, so that what is printed next starts on a clean
+    // line.
+    fn drop(&mut self) {
+        if self.last_draw.is_some() {
+            eprint!("\r\x1b[2K");
+            let _ = io::stderr().flush();
+        }
+    }
+}
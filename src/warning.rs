@@ -0,0 +1,19 @@
+use std::cell::RefCell;
+
+thread_local! {
+    // The warnings issued on this thread since the last `take`
+    static WARNINGS: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+}
+
+// Prints a warning and records it, so that --fail_on_warnings can fail the
+// run once it completes.
+pub(crate) fn warn(message: String) {
+    eprintln!("warning: {message}");
+    WARNINGS.with(|warnings| warnings.borrow_mut().push(message));
+}
+
+// Returns the recorded warnings, in the order they were issued, and clears
+// them.
+pub(crate) fn take() -> Vec<String> {
+    WARNINGS.with(|warnings| warnings.take())
+}
@@ -0,0 +1,75 @@
+use std::process::Command;
+
+fn run_cli(args: &[&str]) -> serde_json::Value {
+    let output = Command::new(env!("CARGO_BIN_EXE_juvix-cairo-vm"))
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    serde_json::from_slice(&output.stdout).unwrap()
+}
+
+#[test]
+fn test_air_public_input_stdout() {
+    let json = run_cli(&[
+        "tests/proof_programs/fibonacci.json",
+        "--proof_mode",
+        "--air_public_input",
+        "-",
+    ]);
+    assert_eq!(json["layout"], "plain");
+}
+
+#[test]
+fn test_air_private_input_stdout() {
+    let json = run_cli(&[
+        "tests/proof_programs/fibonacci.json",
+        "--proof_mode",
+        "--trace_file",
+        "/dev/null",
+        "--memory_file",
+        "/dev/null",
+        "--air_private_input",
+        "-",
+    ]);
+    assert!(json["trace_path"].is_string());
+    assert!(json["memory_path"].is_string());
+}
+
+#[test]
+fn test_emit_output_felts_stdout() {
+    let json = run_cli(&[
+        "tests/input4.json",
+        "--program_input",
+        "tests/input4_input.json",
+        "--proof_mode",
+        "--layout",
+        "small",
+        "--emit_output_felts",
+        "-",
+    ]);
+    assert_eq!(json, serde_json::json!(["4", "16", "9"]));
+}
+
+#[test]
+fn test_runner_error_context() {
+    let output = Command::new(env!("CARGO_BIN_EXE_juvix-cairo-vm"))
+        .args(["tests/builtin_hint.json"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("error running tests/builtin_hint.json (entrypoint main): "));
+}
+
+#[test]
+fn test_json_error_format() {
+    let output = Command::new(env!("CARGO_BIN_EXE_juvix-cairo-vm"))
+        .args(["tests/does_not_exist.json", "--error_format", "json"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stderr).unwrap();
+    assert_eq!(json["kind"], "IO");
+    assert!(json["error"].as_str().unwrap().contains("file system"));
+}